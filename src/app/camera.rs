@@ -1,12 +1,20 @@
 use rand::Rng;
 use tracing::{info, info_span};
-use winit::{dpi::PhysicalPosition, event::WindowEvent, window::Window};
+use winit::{
+    dpi::PhysicalPosition,
+    event::{MouseScrollDelta, WindowEvent},
+    window::Window,
+};
 
 use crate::app::keybinds::{KeyBinds, MoveKey, MoveModifierKey};
-use crate::math::{ToAngle, Vec2, Vec3, rotate_x, rotate_y};
+use crate::math::{ToAngle, Vec2, Vec3};
 use crate::utils::Zero;
 use crate::world::primitives::camera::Camera;
 
+mod controller;
+pub use controller::CameraMode;
+use controller::CameraController;
+
 fn move_key_to_dir(key: MoveKey) -> Vec3 {
     use MoveKey::*;
     match key {
@@ -30,6 +38,7 @@ fn move_modifier_key_to_factor(key: MoveModifierKey) -> f32 {
 pub struct ManualCamera {
     pub cam: Camera,
     pub current_cam_idx: isize,
+    controller: CameraController,
     win_size: Vec2,
     cursor_locked: bool,
 }
@@ -38,6 +47,7 @@ impl ManualCamera {
         Self {
             cam: Camera::default(),
             current_cam_idx: 0,
+            controller: CameraController::new(),
             win_size: Vec2::ONE,
             cursor_locked: false,
         }
@@ -46,6 +56,18 @@ impl ManualCamera {
         info!("Reseting camera");
         self.cam = Camera::default();
         self.current_cam_idx = 0;
+        self.sync_controller();
+    }
+    /// Makes the controller start from the current view, after the camera was moved from outside
+    pub fn sync_controller(&mut self) {
+        self.controller.set_mode(self.controller.mode(), &mut self.cam.pos);
+    }
+    pub fn mode(&self) -> CameraMode {
+        self.controller.mode()
+    }
+    fn next_mode(&mut self) {
+        self.controller.next_mode(&mut self.cam.pos);
+        info!("Changing camera mode to {}", self.controller.mode().name());
     }
     fn reset_current(&mut self) {
         self.current_cam_idx = 0;
@@ -74,6 +96,9 @@ impl ManualCamera {
             win.set_cursor_visible(true);
         }
     }
+    pub fn is_cursor_locked(&self) -> bool {
+        self.cursor_locked
+    }
     pub fn aspect_ratio(&self) -> f32 {
         self.win_size.x() / self.win_size.y()
     }
@@ -81,17 +106,26 @@ impl ManualCamera {
         self.win_size = new_size.into();
     }
     pub fn on_event(&mut self, event: &WindowEvent, win: &Window) {
-        if let WindowEvent::CursorMoved { position, .. } = event {
-            if self.cursor_locked {
+        match event {
+            WindowEvent::CursorMoved { position, .. } if self.cursor_locked => {
                 let pos: Vec2 = (*position).into();
                 let angle = (pos - self.win_size / 2.) / self.win_size.y() * self.cam.fov.rad();
-                self.cam.pos *= rotate_x(angle.y().rad()) * rotate_y(angle.x().rad());
+                self.controller
+                    .rotate(&mut self.cam.pos, angle.x().rad(), angle.y().rad());
                 win.set_cursor_position(PhysicalPosition::new(
                     self.win_size.x() / 2.,
                     self.win_size.y() / 2.,
                 ))
                 .unwrap();
             }
+            WindowEvent::MouseWheel { delta, .. } => {
+                let lines = match delta {
+                    MouseScrollDelta::LineDelta(_, y) => *y,
+                    MouseScrollDelta::PixelDelta(pos) => pos.y as f32 / 20.,
+                };
+                self.controller.zoom(&mut self.cam.pos, lines);
+            }
+            _ => {}
         }
     }
     pub fn update(&mut self, dt: f32, win: &Window, binds: &KeyBinds) {
@@ -107,7 +141,10 @@ impl ManualCamera {
                 off *= move_modifier_key_to_factor(mmk)
             }
         }
-        self.cam.pos += off;
+        self.controller.translate(&mut self.cam.pos, off);
+        let roll = binds.camera_change.roll_right.is_active() as i32
+            - binds.camera_change.roll_left.is_active() as i32;
+        self.controller.roll(&mut self.cam.pos, roll as f32 * dt);
         if off != Vec3::ZERO {
             info!("Moved camera to {} ({:+})", self.cam.pos.trans(), off);
             // let mat = self.cam.matrix(self.aspect_ratio);
@@ -132,5 +169,8 @@ impl ManualCamera {
         if binds.camera_change.reset_pos.is_active() {
             self.reset();
         }
+        if binds.camera_change.next_mode.is_active() {
+            self.next_mode();
+        }
     }
 }
@@ -0,0 +1,138 @@
+use crate::math::{Angle, ToAngle, Transform, Vec3, rotate_x, rotate_y, rotate_z};
+use crate::utils::{Length, array_key};
+use std::f32::consts::FRAC_PI_2;
+
+array_key!(
+    pub enum CameraMode {
+        Fly,
+        Orbit,
+        SixDof,
+    }
+);
+
+/// Keeps the pitch away from the poles, where the yaw is undefined
+const MAX_PITCH: f32 = FRAC_PI_2 - 0.01;
+const DEFAULT_ORBIT_DISTANCE: f32 = 5.;
+const MIN_ORBIT_DISTANCE: f32 = 0.1;
+const ROLL_SPEED: f32 = 1.;
+
+/// Turns the mouse and keys into camera moves, in one of the modes.
+/// Fly and orbit modes keep the camera upright, the 6DOF mode can roll freely
+pub struct CameraController {
+    mode: CameraMode,
+    yaw: f32,
+    pitch: f32,
+    orbit_target: Vec3,
+    orbit_distance: f32,
+}
+impl CameraController {
+    pub fn new() -> Self {
+        Self {
+            mode: CameraMode::Fly,
+            yaw: 0.,
+            pitch: 0.,
+            orbit_target: Vec3::Z * DEFAULT_ORBIT_DISTANCE,
+            orbit_distance: DEFAULT_ORBIT_DISTANCE,
+        }
+    }
+    pub fn mode(&self) -> CameraMode {
+        self.mode
+    }
+    fn rotation(&self) -> Transform {
+        rotate_y(self.yaw.rad()) * rotate_x(self.pitch.rad())
+    }
+    fn forward(&self) -> Vec3 {
+        self.rotation().z()
+    }
+    /// Reads the yaw and pitch from the current view, dropping the roll
+    fn sync_angles(&mut self, pos: Transform) {
+        let forward = pos.z().with_length(1.);
+        self.yaw = forward.x().atan2(forward.z());
+        self.pitch = (-forward.y()).clamp(-1., 1.).asin().clamp(-MAX_PITCH, MAX_PITCH);
+    }
+    fn upright(&self, trans: Vec3) -> Transform {
+        Transform::from_transv(trans) * self.rotation()
+    }
+    /// Switches mode, starting from the current view
+    pub fn set_mode(&mut self, mode: CameraMode, pos: &mut Transform) {
+        self.mode = mode;
+        match mode {
+            CameraMode::Fly => {
+                self.sync_angles(*pos);
+                *pos = self.upright(pos.trans());
+            }
+            CameraMode::Orbit => {
+                self.sync_angles(*pos);
+                self.orbit_distance = DEFAULT_ORBIT_DISTANCE;
+                self.orbit_target = pos.trans() + self.forward() * self.orbit_distance;
+                *pos = self.upright(pos.trans());
+            }
+            CameraMode::SixDof => {}
+        }
+    }
+    pub fn next_mode(&mut self, pos: &mut Transform) {
+        let next = CameraMode::ARRAY[(self.mode as usize + 1) % CameraMode::COUNT];
+        self.set_mode(next, pos);
+    }
+    fn place_orbit(&self, pos: &mut Transform) {
+        *pos = self.upright(self.orbit_target - self.forward() * self.orbit_distance);
+    }
+    /// A mouse move, as angles around the vertical and horizontal axes
+    pub fn rotate(&mut self, pos: &mut Transform, horizontal: Angle, vertical: Angle) {
+        match self.mode {
+            CameraMode::Fly | CameraMode::Orbit => {
+                self.yaw += horizontal.rad();
+                self.pitch = (self.pitch + vertical.rad()).clamp(-MAX_PITCH, MAX_PITCH);
+                if self.mode == CameraMode::Orbit {
+                    self.place_orbit(pos);
+                } else {
+                    *pos = self.upright(pos.trans());
+                }
+            }
+            CameraMode::SixDof => *pos *= rotate_x(vertical) * rotate_y(horizontal),
+        }
+    }
+    /// A move in the camera space
+    pub fn translate(&mut self, pos: &mut Transform, off: Vec3) {
+        *pos += off;
+        if self.mode == CameraMode::Orbit {
+            self.orbit_target = pos.trans() + self.forward() * self.orbit_distance;
+        }
+    }
+    /// Rolls around the view axis, only in 6DOF mode. `amount` is in [-1; 1] per second
+    pub fn roll(&mut self, pos: &mut Transform, amount: f32) {
+        if self.mode == CameraMode::SixDof && amount != 0. {
+            *pos *= rotate_z((amount * ROLL_SPEED).rad());
+        }
+    }
+    /// Zooms the orbit with the mouse wheel, only in orbit mode
+    pub fn zoom(&mut self, pos: &mut Transform, lines: f32) {
+        if self.mode == CameraMode::Orbit {
+            self.orbit_distance = (self.orbit_distance * 0.9f32.powf(lines)).max(MIN_ORBIT_DISTANCE);
+            self.place_orbit(pos);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_switch_keeps_view() {
+        let mut controller = CameraController::new();
+        let mut pos = Transform::ID;
+        controller.set_mode(CameraMode::SixDof, &mut pos);
+        let start = Transform::from_transf(1., 2., 3.)
+            * rotate_y(0.7.rad())
+            * rotate_x((-0.4).rad())
+            * rotate_z(0.3.rad());
+        for mode in [CameraMode::Fly, CameraMode::Orbit] {
+            let mut pos = start;
+            controller.set_mode(mode, &mut pos);
+            assert!((pos.trans() - start.trans()).length() < 1e-4);
+            assert!((pos.z() - start.z()).length() < 1e-4);
+            assert!(pos.x().y().abs() < 1e-4, "the camera should be upright");
+        }
+    }
+}
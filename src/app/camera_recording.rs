@@ -0,0 +1,119 @@
+use crate::app::camera::ManualCamera;
+use crate::app::keybinds::KeyBinds;
+use crate::world::primitives::camera_path::CameraPath;
+use std::path::Path;
+use tracing::{error, info, info_span};
+
+const RECORDING_PATH: &str = "../out/camera_path.txt";
+
+enum RecorderState {
+    Idle,
+    Recording { start: f32, path: CameraPath },
+    Replaying { start: f32, path: CameraPath },
+}
+
+/// Records the manual camera over the scene time, and replays it
+pub struct CameraRecorder {
+    state: RecorderState,
+}
+impl CameraRecorder {
+    pub fn new() -> Self {
+        Self {
+            state: RecorderState::Idle,
+        }
+    }
+    pub fn status(&self) -> &'static str {
+        match self.state {
+            RecorderState::Idle => "idle",
+            RecorderState::Recording { .. } => "recording",
+            RecorderState::Replaying { .. } => "replaying",
+        }
+    }
+    fn is_replaying(&self) -> bool {
+        matches!(self.state, RecorderState::Replaying { .. })
+    }
+    fn stop_recording(path: &CameraPath) {
+        let file = Path::new(RECORDING_PATH);
+        if let Some(dir) = file.parent()
+            && let Err(err) = std::fs::create_dir_all(dir)
+        {
+            error!("Failed to create the directory {dir:?}: {err}");
+            return;
+        }
+        match path.save(file) {
+            Ok(()) => info!(
+                "Saved a camera path of {} samples ({}s) at {file:?}",
+                path.samples().len(),
+                path.duration()
+            ),
+            Err(err) => error!("Failed to save the camera path at {file:?}: {err}"),
+        }
+    }
+    fn toggle_recording(&mut self, time: f32) {
+        self.state = match std::mem::replace(&mut self.state, RecorderState::Idle) {
+            RecorderState::Recording { path, .. } => {
+                Self::stop_recording(&path);
+                RecorderState::Idle
+            }
+            _ => {
+                info!("Recording the camera");
+                RecorderState::Recording {
+                    start: time,
+                    path: CameraPath::new(),
+                }
+            }
+        }
+    }
+    fn toggle_replay(&mut self, time: f32) {
+        self.state = match std::mem::replace(&mut self.state, RecorderState::Idle) {
+            RecorderState::Replaying { .. } => {
+                info!("Stopping the camera replay");
+                RecorderState::Idle
+            }
+            _ => match CameraPath::load(Path::new(RECORDING_PATH)) {
+                Ok(path) if !path.is_empty() => {
+                    info!("Replaying the camera path at {RECORDING_PATH:?}");
+                    RecorderState::Replaying { start: time, path }
+                }
+                Ok(_) => {
+                    error!("The camera path at {RECORDING_PATH:?} is empty");
+                    RecorderState::Idle
+                }
+                Err(err) => {
+                    error!("Failed to load the camera path at {RECORDING_PATH:?}: {err}");
+                    RecorderState::Idle
+                }
+            },
+        }
+    }
+    /// Has to be called after the manual moves, and before the scene update
+    pub fn update(&mut self, camera: &mut ManualCamera, time: f32, binds: &KeyBinds) {
+        let _span = info_span!("camera_recorder").entered();
+        let was_replaying = self.is_replaying();
+        if binds.window_utility.record_camera.is_active() {
+            self.toggle_recording(time);
+        }
+        if binds.window_utility.replay_camera.is_active() {
+            self.toggle_replay(time);
+        }
+        if was_replaying && !self.is_replaying() {
+            // The next moves start from the replayed view
+            camera.sync_controller();
+        }
+        match &mut self.state {
+            RecorderState::Idle => {}
+            RecorderState::Recording { start, path } => {
+                // The time can be reversed or stepped, the samples have to stay in order
+                let t = time - *start;
+                if path.samples().last().is_none_or(|s| s.time < t) {
+                    path.push(t, camera.cam);
+                }
+            }
+            RecorderState::Replaying { start, path } => {
+                if let Some(cam) = path.sample(time - *start) {
+                    camera.cam = cam;
+                }
+            }
+        }
+    }
+}
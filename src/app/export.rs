@@ -0,0 +1,113 @@
+use crate::app::headless::HeadlessRenderer;
+use crate::utils::write_ppm;
+use crate::world::world_builder::WorldsBuilder;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+use tracing::{info, info_span};
+
+/// Parameters of a deterministic export: each frame is rendered at start + i / fps,
+/// whatever the time it takes to render it
+#[derive(Clone, Debug)]
+pub struct ExportSettings {
+    pub width: u32,
+    pub height: u32,
+    pub fps: f32,
+    pub start: f32,
+    pub duration: f32,
+    pub camera_index: isize,
+    pub output_directory: PathBuf,
+    /// Also assemble the frames into an animated gif
+    pub gif: bool,
+}
+impl Default for ExportSettings {
+    fn default() -> Self {
+        Self {
+            width: 1280,
+            height: 720,
+            fps: 30.,
+            start: 0.,
+            duration: 5.,
+            camera_index: 0,
+            output_directory: PathBuf::from("../out/frames"),
+            gif: false,
+        }
+    }
+}
+impl ExportSettings {
+    pub fn frame_count(&self) -> usize {
+        (self.duration * self.fps).round().max(0.) as usize
+    }
+    pub fn frame_time(&self, frame: usize) -> f32 {
+        self.start + frame as f32 / self.fps
+    }
+}
+
+struct GifOutput {
+    encoder: gif::Encoder<BufWriter<File>>,
+    delay: u16,
+}
+impl GifOutput {
+    fn new(settings: &ExportSettings) -> std::io::Result<Self> {
+        let path = settings.output_directory.join("animation.gif");
+        info!("Writing the gif at {path:?}");
+        let file = BufWriter::new(File::create(path)?);
+        let mut encoder = gif::Encoder::new(
+            file,
+            settings.width as u16,
+            settings.height as u16,
+            &[],
+        )
+        .map_err(std::io::Error::other)?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(std::io::Error::other)?;
+        Ok(Self {
+            encoder,
+            // In hundredths of a second
+            delay: (100. / settings.fps).round() as u16,
+        })
+    }
+    fn push(&mut self, width: u32, height: u32, mut rgba: Vec<u8>) -> std::io::Result<()> {
+        let mut frame = gif::Frame::from_rgba_speed(width as u16, height as u16, &mut rgba, 10);
+        frame.delay = self.delay;
+        self.encoder
+            .write_frame(&frame)
+            .map_err(std::io::Error::other)
+    }
+}
+
+/// Renders the frames offscreen and writes them as a numbered image sequence.
+/// Returns the number of frames written
+pub fn export_frames(
+    builder_fun: impl FnMut() -> WorldsBuilder,
+    settings: &ExportSettings,
+) -> std::io::Result<usize> {
+    let _span = info_span!("export").entered();
+    let count = settings.frame_count();
+    info!(
+        "Exporting {count} frames of {}x{} at {} fps, camera {}",
+        settings.width, settings.height, settings.fps, settings.camera_index
+    );
+    std::fs::create_dir_all(&settings.output_directory)?;
+    if settings.gif && (settings.width > u16::MAX as u32 || settings.height > u16::MAX as u32) {
+        return Err(std::io::Error::other("Resolution too big for a gif"));
+    }
+
+    let mut renderer = HeadlessRenderer::new(builder_fun, settings.width, settings.height)
+        .map_err(std::io::Error::other)?;
+    renderer.camera.current_cam_idx = settings.camera_index;
+    let mut gif = settings.gif.then(|| GifOutput::new(settings)).transpose()?;
+
+    for i in 0..count {
+        let pixels = renderer.render(settings.frame_time(i));
+        let path = settings.output_directory.join(format!("frame-{i:05}.ppm"));
+        write_ppm(&path, settings.width, settings.height, &pixels)?;
+        if let Some(gif) = &mut gif {
+            gif.push(settings.width, settings.height, pixels)?;
+        }
+        info!("Exported frame {}/{count}", i + 1);
+    }
+    info!("Exported {count} frames in {:?}", settings.output_directory);
+    Ok(count)
+}
@@ -0,0 +1,138 @@
+use crate::render_registry::gpu_timer::GpuTimer;
+use crate::render_registry::vertex::REQUIRED_VERTEX_ATTRIBUTES;
+use std::fmt::{Display, Formatter};
+use tracing::{info, warn};
+
+/// Asked when the adapter allows it, only `REQUIRED_VERTEX_ATTRIBUTES` are needed
+const WANTED_VERTEX_ATTRIBUTES: u32 = 32;
+
+/// Optional features, each one enabling an option
+const OPTIONAL_FEATURES: wgpu::Features =
+    wgpu::Features::POLYGON_MODE_LINE.union(GpuTimer::FEATURES);
+
+#[derive(Debug)]
+pub enum StartupError {
+    NoAdapter(wgpu::RequestAdapterError),
+    NoDevice(wgpu::RequestDeviceError),
+    /// The adapter doesn't reach even the downlevel limits
+    UnsupportedLimits,
+    MissingLimit {
+        name: &'static str,
+        required: u32,
+        supported: u32,
+    },
+    EventLoop(winit::error::EventLoopError),
+}
+impl Display for StartupError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoAdapter(err) => write!(f, "no graphics adapter found: {err}"),
+            Self::NoDevice(err) => write!(f, "failed to open the graphics device: {err}"),
+            Self::UnsupportedLimits => write!(f, "the graphics adapter limits are too low"),
+            Self::MissingLimit {
+                name,
+                required,
+                supported,
+            } => write!(
+                f,
+                "the graphics adapter supports {supported} {name}, {required} are needed"
+            ),
+            Self::EventLoop(err) => write!(f, "failed to run the event loop: {err}"),
+        }
+    }
+}
+impl std::error::Error for StartupError {}
+
+pub fn request_adapter(
+    instance: &wgpu::Instance,
+    surface: Option<&wgpu::Surface>,
+    force_fallback_adapter: bool,
+) -> Result<wgpu::Adapter, StartupError> {
+    let options = wgpu::RequestAdapterOptions {
+        compatible_surface: surface,
+        force_fallback_adapter,
+        ..Default::default()
+    };
+    pollster::block_on(instance.request_adapter(&options)).map_err(StartupError::NoAdapter)
+}
+
+/// The default limits when the adapter reaches them, else the downlevel ones
+fn clamp_limits(adapter: &wgpu::Adapter) -> Result<wgpu::Limits, StartupError> {
+    let supported = adapter.limits();
+    let mut limits = wgpu::Limits::default();
+    if !limits.check_limits(&supported) {
+        warn!("The adapter doesn't reach the default limits, using the downlevel ones");
+        limits = wgpu::Limits::downlevel_defaults().using_resolution(supported.clone());
+        if !limits.check_limits(&supported) {
+            return Err(StartupError::UnsupportedLimits);
+        }
+    }
+    if supported.max_vertex_attributes < REQUIRED_VERTEX_ATTRIBUTES {
+        return Err(StartupError::MissingLimit {
+            name: "vertex attributes",
+            required: REQUIRED_VERTEX_ATTRIBUTES,
+            supported: supported.max_vertex_attributes,
+        });
+    }
+    limits.max_vertex_attributes = WANTED_VERTEX_ATTRIBUTES.min(supported.max_vertex_attributes);
+    Ok(limits)
+}
+
+fn describe_missing(missing: wgpu::Features) -> String {
+    [
+        (wgpu::Features::POLYGON_MODE_LINE, "wireframes"),
+        (GpuTimer::FEATURES, "GPU timings"),
+    ]
+    .into_iter()
+    .filter(|(features, _)| missing.intersects(*features))
+    .map(|(features, option)| format!("{features:?} ({option} disabled)"))
+    .collect::<Vec<_>>()
+    .join(", ")
+}
+
+/// The adapter and device used to render, with the optional features it lacks
+pub struct Gpu {
+    pub adapter: wgpu::Adapter,
+    pub device: wgpu::Device,
+    pub queue: wgpu::Queue,
+    pub missing_features: wgpu::Features,
+}
+impl Gpu {
+    /// Retries with a software fallback adapter when the default one can't be used
+    pub fn new(instance: &wgpu::Instance) -> Result<Self, StartupError> {
+        Self::with_adapter(instance, false).or_else(|err| {
+            warn!("{err}, trying a software fallback adapter");
+            Self::with_adapter(instance, true)
+        })
+    }
+    fn with_adapter(
+        instance: &wgpu::Instance,
+        force_fallback_adapter: bool,
+    ) -> Result<Self, StartupError> {
+        let adapter = request_adapter(instance, None, force_fallback_adapter)?;
+        info!("Using adapter {:?}", adapter.get_info().name);
+        let required_limits = clamp_limits(&adapter)?;
+        let supported = adapter.features();
+        let missing_features = OPTIONAL_FEATURES - supported;
+        if !missing_features.is_empty() {
+            warn!(
+                "Unavailable features: {}",
+                describe_missing(missing_features)
+            );
+        }
+        let desc = wgpu::DeviceDescriptor {
+            label: Some("Device get desc"),
+            required_limits,
+            required_features: OPTIONAL_FEATURES & supported,
+            ..Default::default()
+        };
+        let (device, queue) =
+            pollster::block_on(adapter.request_device(&desc)).map_err(StartupError::NoDevice)?;
+        Ok(Self {
+            adapter,
+            device,
+            queue,
+            missing_features,
+        })
+    }
+}
@@ -0,0 +1,100 @@
+use crate::app::camera::ManualCamera;
+use crate::app::gpu::{Gpu, StartupError};
+use crate::app::scene::{Scene, SceneFrame, ViewCamera};
+use crate::profiler::Profiler;
+use crate::render_registry::offscreen::OffscreenTarget;
+use crate::render_registry::registry::{PipelinesRegistry, ViewportRect};
+use crate::world::lod::FrameBudget;
+use crate::world::world_builder::{WorldId, WorldsBuilder};
+use tracing::{info, info_span};
+use winit::dpi::PhysicalSize;
+
+/// Renders a scene without any window, into an offscreen texture
+pub struct HeadlessRenderer {
+    pub device: wgpu::Device,
+    pub queue: wgpu::Queue,
+    pub scene: Scene,
+    pub camera: ManualCamera,
+    pub target: OffscreenTarget,
+    pub registry: PipelinesRegistry,
+}
+impl HeadlessRenderer {
+    pub fn new(
+        mut builder_fun: impl FnMut() -> WorldsBuilder,
+        width: u32,
+        height: u32,
+    ) -> Result<Self, StartupError> {
+        let _span = info_span!("headless").entered();
+        info!("Creating headless renderer");
+        let instance = wgpu::Instance::default();
+        let Gpu { device, queue, .. } = Gpu::new(&instance)?;
+        let scene = Scene::new(&mut builder_fun);
+        let target = OffscreenTarget::new(&device, width, height);
+        let registry = PipelinesRegistry::new(&device, &target.config, &scene.allocs);
+        let mut camera = ManualCamera::new();
+        camera.on_resize(PhysicalSize::new(width, height));
+        Ok(Self {
+            device,
+            queue,
+            scene,
+            camera,
+            target,
+            registry,
+        })
+    }
+    pub fn size(&self) -> (u32, u32) {
+        (self.target.config.width, self.target.config.height)
+    }
+    /// Builds worlds and adds them to the scene, as `App::add_worlds`
+    pub fn add_worlds(
+        &mut self,
+        build: impl FnOnce(WorldsBuilder) -> WorldsBuilder,
+    ) -> Vec<WorldId> {
+        let first = self.scene.allocs.len();
+        let ids = self.scene.add_worlds(build);
+        for alloc in &self.scene.allocs[first..] {
+            self.registry
+                .add_world(&self.device, &self.target.config, alloc);
+        }
+        ids
+    }
+    /// Drops a world and frees its GPU buffers, as `App::remove_world`
+    pub fn remove_world(&mut self, id: WorldId) -> bool {
+        if !self.scene.remove_world(id) {
+            return false;
+        }
+        self.registry.remove_world(&self.device, id.get());
+        true
+    }
+    /// Updates the scene at the given time and returns the RGBA8 pixels of the frame
+    pub fn render(&mut self, time: f32) -> Vec<u8> {
+        let _span = info_span!("headless_render").entered();
+        self.registry.set_time(&self.queue, time);
+        let camera = ViewCamera {
+            index: self.camera.current_cam_idx,
+            aspect_ratio: self.camera.aspect_ratio(),
+        };
+        self.scene.update(
+            &mut self.registry,
+            &self.queue,
+            SceneFrame {
+                time,
+                manual_camera: &self.camera,
+                cameras: &[camera],
+                budget: FrameBudget::on_time(1. / 60.),
+            },
+            &mut Profiler::new(),
+        );
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Headless render encoder"),
+            });
+        let viewport = ViewportRect::full(&self.target.config);
+        self.registry
+            .render(&mut encoder, &self.target.view, false, &[viewport], None);
+        self.queue.submit([encoder.finish()]);
+        self.target.read_pixels(&self.device, &self.queue)
+    }
+}
@@ -0,0 +1,70 @@
+use crate::app::App;
+use crate::settings::current_perf_level;
+
+fn on_off(active: bool) -> &'static str {
+    if active { "on" } else { "off" }
+}
+
+fn hud_lines(app: &App) -> Vec<String> {
+    let delta = app.clock.last_delta();
+    let cam = app.scene.get_cam(app.camera.current_cam_idx);
+    let pos = cam.pos.trans().to_array();
+    let debug = &app.key_binds.window_debug;
+    let culling = app.scene.cull_stats();
+    vec![
+        format!(
+            "fps {:.1}/{:.0}  delta {:.1} ms",
+            1. / delta.max(f32::EPSILON),
+            app.clock.target_fps(),
+            delta * 1000.
+        ),
+        format!(
+            "time {:.2}  speed x{}{}",
+            app.clock.time(),
+            app.clock.speed(),
+            if app.clock.is_paused() { "  paused" } else { "" }
+        ),
+        format!(
+            "camera {} ({})  pos ({:.2}, {:.2}, {:.2})  layout {}",
+            app.camera.current_cam_idx,
+            app.camera.mode().name(),
+            pos[0],
+            pos[1],
+            pos[2],
+            app.viewports.layout().name
+        ),
+        format!(
+            "perf {}  wires {}  profiling {}  camera path {}",
+            current_perf_level().name(),
+            if app.missing_features.contains(wgpu::Features::POLYGON_MODE_LINE) {
+                "n/a"
+            } else {
+                on_off(debug.show_wires.is_active())
+            },
+            on_off(app.profiler.is_enabled()),
+            app.camera_recorder.status()
+        ),
+        format!(
+            "worlds {} shown  {} culled  {} too far",
+            culling.shown, culling.culled, culling.too_far
+        ),
+        match &app.picker.selection {
+            Some(pick) => format!("selected {}", pick.describe()),
+            None => "selected nothing".to_string(),
+        },
+    ]
+}
+
+/// Draws the hud over the frame when it is toggled on
+pub fn render_hud(app: &mut App, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+    if !app.key_binds.window_debug.show_hud.is_active() {
+        return;
+    }
+    let lines = hud_lines(app);
+    let Some(holder) = &mut app.window else {
+        return;
+    };
+    let size = (holder.surface_config.width, holder.surface_config.height);
+    holder.hud.set_text(&app.device, &app.queue, size, &lines);
+    holder.hud.render(encoder, view);
+}
@@ -1,4 +1,8 @@
 use crate::utils::array_key;
+
+mod config;
+use std::path::Path;
+use tracing::{error, info, warn};
 use winit::event::{ElementState, KeyEvent, WindowEvent};
 use winit::keyboard::{KeyCode, PhysicalKey};
 
@@ -30,6 +34,19 @@ macro_rules! make_folder {
                     f(&mut self.$attr_name);
                 )*
             }
+            fn for_each_named(&self, folder: &str, f: &mut impl FnMut(String, &KeyBind)) {
+                $(
+                    f(format!("{folder}.{}", stringify!($attr_name)), &self.$attr_name);
+                )*
+            }
+            fn get_mut(&mut self, name: &str) -> Option<&mut KeyBind> {
+                match name {
+                    $(
+                        stringify!($attr_name) => Some(&mut self.$attr_name),
+                    )*
+                    _ => None,
+                }
+            }
         }
     };
 }
@@ -41,15 +58,37 @@ make_folder!(CameraChanges:
     reset_cam = KeyBind::new(Trigger::Pressed, vec![KeyCode::KeyO]);
     reset_pos = KeyBind::new(Trigger::Pressed, vec![KeyCode::KeyR]);
     toggle_lock = KeyBind::new(Trigger::Pressed, vec![KeyCode::KeyU]);
+    next_mode = KeyBind::new(Trigger::Pressed, vec![KeyCode::KeyV]);
+    roll_left = KeyBind::new(Trigger::AllActive, vec![KeyCode::KeyZ]);
+    roll_right = KeyBind::new(Trigger::AllActive, vec![KeyCode::KeyC]);
 );
 
 make_folder!(WindowDebug:
     show_fps = KeyBind::new(Trigger::Pressed, vec![KeyCode::F3, KeyCode::KeyX]);
     show_wires = KeyBind::new(Trigger::Toggle(false), vec![KeyCode::F3, KeyCode::KeyG]);
+    show_hud = KeyBind::new(Trigger::Toggle(false), vec![KeyCode::F1]);
+    profile = KeyBind::new(Trigger::Toggle(false), vec![KeyCode::F3, KeyCode::KeyK]);
+    profile_report = KeyBind::new(Trigger::Pressed, vec![KeyCode::F3, KeyCode::KeyI]);
+);
+
+make_folder!(TimeControl:
+    toggle_pause = KeyBind::new(Trigger::Pressed, vec![KeyCode::Space]);
+    speed_up = KeyBind::new(Trigger::Pressed, vec![KeyCode::BracketRight]);
+    slow_down = KeyBind::new(Trigger::Pressed, vec![KeyCode::BracketLeft]);
+    reverse = KeyBind::new(Trigger::Pressed, vec![KeyCode::Backspace]);
+    reset_speed = KeyBind::new(Trigger::Pressed, vec![KeyCode::Digit0]);
+    step_forward = KeyBind::new(Trigger::Pressed, vec![KeyCode::Period]);
+    step_backward = KeyBind::new(Trigger::Pressed, vec![KeyCode::Comma]);
 );
 
 make_folder!(WindowUtility:
     screenshot = KeyBind::new(Trigger::Pressed, vec![KeyCode::F5, KeyCode::F6]);
+    reload_scene = KeyBind::new(Trigger::Pressed, vec![KeyCode::F3, KeyCode::KeyT]);
+    more_details = KeyBind::new(Trigger::Pressed, vec![KeyCode::F3, KeyCode::Equal]);
+    more_perf = KeyBind::new(Trigger::Pressed, vec![KeyCode::F3, KeyCode::Minus]);
+    record_camera = KeyBind::new(Trigger::Pressed, vec![KeyCode::F7]);
+    replay_camera = KeyBind::new(Trigger::Pressed, vec![KeyCode::F8]);
+    next_layout = KeyBind::new(Trigger::Pressed, vec![KeyCode::F4]);
 );
 
 array_key!(
@@ -201,6 +240,7 @@ pub struct KeyBinds {
     pub camera_moves: [KeyBind; MoveKey::COUNT],
     pub camera_move_modifiers: [KeyBind; MoveModifierKey::COUNT],
     pub camera_change: CameraChanges,
+    pub time_control: TimeControl,
     pub window_debug: WindowDebug,
     pub window_utility: WindowUtility,
 }
@@ -213,6 +253,7 @@ impl KeyBinds {
             f(b)
         }
         self.camera_change.bind_map(f);
+        self.time_control.bind_map(f);
         self.window_debug.bind_map(f);
         self.window_utility.bind_map(f);
     }
@@ -237,6 +278,67 @@ impl KeyBinds {
     pub fn next_frame(&mut self) {
         self.bind_map(&|b| b.next_frame());
     }
+    /// Calls f with the config name of every bind, like `camera_change.next_cam`
+    fn for_each_named(&self, f: &mut impl FnMut(String, &KeyBind)) {
+        for k in MoveKey::ARRAY {
+            f(format!("camera_moves.{}", k.name()), &self.camera_moves[k as usize]);
+        }
+        for k in MoveModifierKey::ARRAY {
+            f(
+                format!("camera_move_modifiers.{}", k.name()),
+                &self.camera_move_modifiers[k as usize],
+            );
+        }
+        self.camera_change.for_each_named("camera_change", f);
+        self.time_control.for_each_named("time_control", f);
+        self.window_debug.for_each_named("window_debug", f);
+        self.window_utility.for_each_named("window_utility", f);
+    }
+    fn get_mut(&mut self, name: &str) -> Option<&mut KeyBind> {
+        let (folder, bind) = name.split_once('.')?;
+        match folder {
+            "camera_moves" => MoveKey::ARRAY
+                .into_iter()
+                .find(|k| k.name() == bind)
+                .map(|k| &mut self.camera_moves[k as usize]),
+            "camera_move_modifiers" => MoveModifierKey::ARRAY
+                .into_iter()
+                .find(|k| k.name() == bind)
+                .map(|k| &mut self.camera_move_modifiers[k as usize]),
+            "camera_change" => self.camera_change.get_mut(bind),
+            "time_control" => self.time_control.get_mut(bind),
+            "window_debug" => self.window_debug.get_mut(bind),
+            "window_utility" => self.window_utility.get_mut(bind),
+            _ => None,
+        }
+    }
+    /// Loads the binds from a config file, over the defaults.
+    /// Writes the defaults when there is no file, so they can be edited
+    pub fn load_or_default(path: &Path) -> Self {
+        let mut binds = Self::base_binds();
+        match std::fs::read_to_string(path) {
+            Ok(content) => {
+                info!("Loading key binds from {path:?}");
+                for err in binds.apply_config(&content) {
+                    error!("Invalid key binds config {path:?}: {err}");
+                }
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                info!("No key binds config at {path:?}, writing the defaults");
+                if let Err(err) = binds.save(path) {
+                    warn!("Failed to write the key binds config {path:?}: {err}");
+                }
+            }
+            Err(err) => error!("Failed to read the key binds config {path:?}: {err}, using the defaults"),
+        }
+        binds
+    }
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, self.to_config())
+    }
     pub fn base_binds() -> Self {
         Self {
             camera_moves: MoveKey::ARRAY.map(|k| {
@@ -256,6 +358,7 @@ impl KeyBinds {
                 }])
             }),
             camera_change: CameraChanges::new(),
+            time_control: TimeControl::new(),
             window_debug: WindowDebug::new(),
             window_utility: WindowUtility::new(),
         }
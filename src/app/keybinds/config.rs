@@ -0,0 +1,194 @@
+//! Text format of the key binds, one bind per line:
+//! `window_debug.show_wires = Toggle(false): F3 KeyG`
+//! Keys are winit key codes, `#` starts a comment.
+
+use super::{KeyBind, KeyBinds, Trigger};
+use std::fmt::{Display, Formatter};
+use winit::keyboard::KeyCode;
+
+macro_rules! key_codes {
+    (
+        $($code: ident)*
+    ) => {
+        fn key_code_from_name(name: &str) -> Option<KeyCode> {
+            match name {
+                $(
+                    stringify!($code) => Some(KeyCode::$code),
+                )*
+                _ => None,
+            }
+        }
+    };
+}
+
+key_codes!(
+    Backquote Backslash BracketLeft BracketRight Comma Equal Minus Period Quote Semicolon Slash
+    IntlBackslash IntlRo IntlYen
+    Digit0 Digit1 Digit2 Digit3 Digit4 Digit5 Digit6 Digit7 Digit8 Digit9
+    KeyA KeyB KeyC KeyD KeyE KeyF KeyG KeyH KeyI KeyJ KeyK KeyL KeyM
+    KeyN KeyO KeyP KeyQ KeyR KeyS KeyT KeyU KeyV KeyW KeyX KeyY KeyZ
+    AltLeft AltRight ControlLeft ControlRight ShiftLeft ShiftRight SuperLeft SuperRight
+    Backspace CapsLock ContextMenu Enter Space Tab Escape
+    Delete End Help Home Insert PageDown PageUp PrintScreen ScrollLock Pause
+    ArrowDown ArrowLeft ArrowRight ArrowUp
+    NumLock Numpad0 Numpad1 Numpad2 Numpad3 Numpad4 Numpad5 Numpad6 Numpad7 Numpad8 Numpad9
+    NumpadAdd NumpadSubtract NumpadMultiply NumpadDivide NumpadDecimal NumpadEnter NumpadEqual
+    F1 F2 F3 F4 F5 F6 F7 F8 F9 F10 F11 F12 F13 F14 F15 F16 F17 F18 F19 F20 F21 F22 F23 F24
+);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeyBindsConfigError {
+    Syntax { line: usize },
+    UnknownBind { line: usize, name: String },
+    UnknownTrigger { line: usize, trigger: String },
+    UnknownKey { line: usize, key: String },
+    NoKeys { line: usize },
+}
+impl Display for KeyBindsConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Syntax { line } => write!(
+                f,
+                "line {line}: expected `name = Trigger: Key Key ...`"
+            ),
+            Self::UnknownBind { line, name } => write!(f, "line {line}: unknown bind `{name}`"),
+            Self::UnknownTrigger { line, trigger } => write!(
+                f,
+                "line {line}: unknown trigger `{trigger}`, expected AllActive, AllInactive, Pressed, Released, Toggle or Toggle(true)"
+            ),
+            Self::UnknownKey { line, key } => write!(f, "line {line}: unknown key code `{key}`"),
+            Self::NoKeys { line } => write!(f, "line {line}: a bind needs at least one key"),
+        }
+    }
+}
+
+impl Trigger {
+    fn parse(s: &str) -> Option<Self> {
+        Some(match s {
+            "AllActive" => Self::AllActive,
+            "AllInactive" => Self::AllInactive,
+            "Pressed" => Self::Pressed,
+            "Released" => Self::Released,
+            "Toggle" | "Toggle(false)" => Self::Toggle(false),
+            "Toggle(true)" => Self::Toggle(true),
+            _ => return None,
+        })
+    }
+}
+
+fn parse_line(line: usize, content: &str) -> Result<(&str, KeyBind), KeyBindsConfigError> {
+    let (name, value) = content
+        .split_once('=')
+        .ok_or(KeyBindsConfigError::Syntax { line })?;
+    let (trigger, keys) = value
+        .split_once(':')
+        .ok_or(KeyBindsConfigError::Syntax { line })?;
+    let trigger = trigger.trim();
+    let trigger = Trigger::parse(trigger).ok_or_else(|| KeyBindsConfigError::UnknownTrigger {
+        line,
+        trigger: trigger.to_string(),
+    })?;
+    let keys = keys
+        .split_whitespace()
+        .map(|key| {
+            key_code_from_name(key).ok_or_else(|| KeyBindsConfigError::UnknownKey {
+                line,
+                key: key.to_string(),
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if keys.is_empty() {
+        return Err(KeyBindsConfigError::NoKeys { line });
+    }
+    Ok((name.trim(), KeyBind::new(trigger, keys)))
+}
+
+impl KeyBinds {
+    /// Applies every valid line of the config over the current binds, returns the errors of the others
+    pub fn apply_config(&mut self, config: &str) -> Vec<KeyBindsConfigError> {
+        let mut errors = Vec::new();
+        for (i, content) in config.lines().enumerate() {
+            let line = i + 1;
+            let content = content.split('#').next().unwrap_or_default().trim();
+            if content.is_empty() {
+                continue;
+            }
+            match parse_line(line, content) {
+                Ok((name, bind)) => match self.get_mut(name) {
+                    Some(old) => *old = bind,
+                    None => errors.push(KeyBindsConfigError::UnknownBind {
+                        line,
+                        name: name.to_string(),
+                    }),
+                },
+                Err(err) => errors.push(err),
+            }
+        }
+        errors
+    }
+    pub fn to_config(&self) -> String {
+        let mut config = String::from("# Key binds, `name = Trigger: Key Key ...`\n");
+        self.for_each_named(&mut |name, bind| {
+            let trigger = match bind.trigger {
+                Trigger::Toggle(state) => format!("Toggle({state})"),
+                ref trigger => format!("{trigger:?}"),
+            };
+            let keys = bind
+                .keys
+                .iter()
+                .map(|k| format!("{:?}", k.code))
+                .collect::<Vec<_>>()
+                .join(" ");
+            config.push_str(&format!("{name} = {trigger}: {keys}\n"));
+        });
+        config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let config = KeyBinds::base_binds().to_config();
+        let mut binds = KeyBinds::base_binds();
+        assert_eq!(binds.apply_config(&config), vec![]);
+        assert_eq!(binds.to_config(), config);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut binds = KeyBinds::base_binds();
+        let errors = binds.apply_config(
+            "
+            # azerty
+            camera_moves.Forward = AllActive: KeyZ
+            camera_moves.Upward = AllActive: KeyE
+            camera_change.next_cam = Pressed: KeyM Semicolon2
+            window_debug.show_fps = Sometimes: F3
+            window_debug.show_wires
+            ",
+        );
+        assert_eq!(errors, vec![
+            KeyBindsConfigError::UnknownBind {
+                line: 4,
+                name: "camera_moves.Upward".to_string()
+            },
+            KeyBindsConfigError::UnknownKey {
+                line: 5,
+                key: "Semicolon2".to_string()
+            },
+            KeyBindsConfigError::UnknownTrigger {
+                line: 6,
+                trigger: "Sometimes".to_string()
+            },
+            KeyBindsConfigError::Syntax { line: 7 },
+        ]);
+        assert!(
+            binds
+                .to_config()
+                .contains("camera_moves.Forward = AllActive: KeyZ\n")
+        );
+    }
+}
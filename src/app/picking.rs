@@ -0,0 +1,68 @@
+use crate::app::App;
+use crate::math::Vec2;
+use crate::utils::Zero;
+use crate::world::picking::{Pick, Ray};
+use tracing::info;
+use winit::event::{ElementState, MouseButton, WindowEvent};
+
+/// The object clicked last, kept for the inspectors
+pub struct Picker {
+    cursor: Vec2,
+    pub selection: Option<Pick>,
+}
+impl Picker {
+    pub fn new() -> Self {
+        Self {
+            cursor: Vec2::ZERO,
+            selection: None,
+        }
+    }
+}
+
+/// Casts a ray from the pixel at `cursor` through the viewport containing it
+fn pick_at(app: &App, cursor: Vec2) -> Option<Pick> {
+    let holder = app.window.as_ref()?;
+    let (width, height) = (holder.surface_config.width, holder.surface_config.height);
+    let layout = app.viewports.layout();
+    let (rect, camera) = layout
+        .rects(width, height)
+        .into_iter()
+        .zip(layout.cameras(&app.camera, width, height))
+        .find(|(r, _)| {
+            (r.x..r.x + r.width).contains(&cursor.x())
+                && (r.y..r.y + r.height).contains(&cursor.y())
+        })?;
+    let ndc = Vec2::new(
+        (cursor.x() - rect.x) / rect.width * 2. - 1.,
+        1. - (cursor.y() - rect.y) / rect.height * 2.,
+    );
+    let cam = app.scene.get_cam(camera.index);
+    app.scene
+        .pick(Ray::from_camera(&cam, camera.aspect_ratio, ndc))
+}
+
+/// Selects what is under the cursor on left click, or at the center while it is locked
+pub fn check_picking(app: &mut App, event: &WindowEvent) {
+    match event {
+        WindowEvent::CursorMoved { position, .. } => app.picker.cursor = (*position).into(),
+        WindowEvent::MouseInput {
+            state: ElementState::Pressed,
+            button: MouseButton::Left,
+            ..
+        } => {
+            let cursor = match &app.window {
+                Some(holder) if app.camera.is_cursor_locked() => Vec2::new(
+                    holder.surface_config.width as f32 / 2.,
+                    holder.surface_config.height as f32 / 2.,
+                ),
+                _ => app.picker.cursor,
+            };
+            app.picker.selection = pick_at(app, cursor);
+            match &app.picker.selection {
+                Some(pick) => info!("Picked {}", pick.describe()),
+                None => info!("Picked nothing"),
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,46 @@
+use crate::app::App;
+use std::path::Path;
+use tracing::{error, info};
+use winit::event::WindowEvent;
+
+const CSV_PATH: &str = "../out/profile.csv";
+
+/// Follows the profiling toggle, and prints and saves the report when asked
+pub fn check_profiling(app: &mut App, event: &WindowEvent) {
+    if !matches!(event, WindowEvent::RedrawRequested) {
+        return;
+    }
+    let binds = &app.key_binds.window_debug;
+    let enabled = binds.profile.is_active();
+    if enabled != app.profiler.is_enabled() {
+        info!(
+            "{} profiling",
+            if enabled { "Starting" } else { "Stopping" }
+        );
+        app.profiler.set_enabled(enabled);
+    }
+    if binds.profile_report.is_active() {
+        if !app.profiler.is_enabled() {
+            info!("Profiling is off, nothing to report");
+            return;
+        }
+        info!("Profiling report:\n{}", app.profiler.report());
+        match app.profiler.write_csv(Path::new(CSV_PATH)) {
+            Ok(()) => info!("Saved the profiling report at {CSV_PATH}"),
+            Err(err) => error!("Failed to save the profiling report at {CSV_PATH}: {err}"),
+        }
+    }
+}
+
+/// Collects the GPU timings read so far and closes the frame
+pub fn end_profiled_frame(app: &mut App) {
+    if !app.profiler.is_enabled() {
+        return;
+    }
+    if let Some(timer) = app.window.as_mut().and_then(|h| h.gpu_timer.as_mut()) {
+        for (label, ms) in timer.read(&app.device) {
+            app.profiler.record(|| label, ms);
+        }
+    }
+    app.profiler.end_frame();
+}
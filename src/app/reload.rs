@@ -0,0 +1,59 @@
+use crate::app::App;
+use crate::app::scene::Scene;
+use crate::render_registry::registry::PipelinesRegistry;
+use crate::settings::{PerfLevel, current_perf_level, set_perf_level};
+use tracing::{info, info_span};
+use winit::event::WindowEvent;
+
+/// Calls the builder again and recreates the scene and the pipelines.
+/// The manual camera is untouched, so the view and the selected camera are kept
+fn rebuild(app: &mut App, keep_shaders: bool) {
+    app.scene = Scene::new(&mut app.builder_fun);
+    if let Some(holder) = &mut app.window {
+        holder.registry = if keep_shaders {
+            holder
+                .registry
+                .rebuild(&app.device, &holder.surface_config, &app.scene.allocs)
+        } else {
+            PipelinesRegistry::new(&app.device, &holder.surface_config, &app.scene.allocs)
+        };
+    }
+}
+
+pub fn reload_scene(app: &mut App) {
+    let _span = info_span!("reload_scene").entered();
+    info!("Reloading scene");
+    rebuild(app, true);
+    info!("Succesfully reloaded scene");
+}
+
+/// Regenerates the prefabs and the shaders at the new level
+pub fn change_perf_level(app: &mut App, level: PerfLevel) {
+    let _span = info_span!("change_perf_level").entered();
+    set_perf_level(level);
+    app.clock.update_target_fps();
+    rebuild(app, false);
+    if let Some(reloader) = &mut app.shader_reloader {
+        reloader.invalidate();
+    }
+}
+
+pub fn check_reload(app: &mut App, event: &WindowEvent) {
+    if !matches!(event, WindowEvent::RedrawRequested) {
+        return;
+    }
+    let binds = &app.key_binds.window_utility;
+    let level = current_perf_level();
+    let new_level = if binds.more_details.is_active() {
+        level.more_details()
+    } else if binds.more_perf.is_active() {
+        level.more_perf()
+    } else {
+        level
+    };
+    if new_level != level {
+        change_perf_level(app, new_level);
+    } else if binds.reload_scene.is_active() {
+        reload_scene(app);
+    }
+}
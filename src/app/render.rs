@@ -1,5 +1,8 @@
 use crate::app::App;
-use tracing::{error, info_span};
+use crate::app::hud::render_hud;
+use crate::app::profiling::end_profiled_frame;
+use crate::app::screenshots::check_screenshot;
+use tracing::{error, info_span, warn};
 use winit::event::WindowEvent;
 
 pub fn check_render(app: &mut App, event: &WindowEvent) {
@@ -14,7 +17,26 @@ pub fn check_render(app: &mut App, event: &WindowEvent) {
         return;
     };
 
-    let output = holder.surface.get_current_texture().unwrap();
+    if holder.is_hidden() {
+        return;
+    }
+    let output = match holder.surface.get_current_texture() {
+        Ok(output) => output,
+        Err(err @ (wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated)) => {
+            warn!("Surface {err}, configuring it again");
+            holder.needs_reconfigure = true;
+            holder.window.request_redraw();
+            return;
+        }
+        Err(wgpu::SurfaceError::Timeout) => {
+            warn!("Timed out waiting for the surface, skipping the frame");
+            return;
+        }
+        Err(err) => {
+            error!("Can't get the surface texture, skipping the frame: {err}");
+            return;
+        }
+    };
     let view = output
         .texture
         .create_view(&wgpu::TextureViewDescriptor::default());
@@ -23,7 +45,36 @@ pub fn check_render(app: &mut App, event: &WindowEvent) {
         .create_command_encoder(&wgpu::CommandEncoderDescriptor {
             label: Some("Render encoder"),
         });
-    holder.registry.render(&mut encoder, &view, app.key_binds.window_debug.show_wires.is_active());
+    let viewports = app
+        .viewports
+        .layout()
+        .rects(holder.surface_config.width, holder.surface_config.height);
+    let timer = holder
+        .gpu_timer
+        .as_mut()
+        .filter(|_| app.profiler.is_enabled());
+    app.profiler.time(
+        || "render".to_string(),
+        || {
+            holder.registry.render(
+                &mut encoder,
+                &view,
+                app.key_binds.window_debug.show_wires.is_active(),
+                &viewports,
+                timer,
+            )
+        },
+    );
+    if let Some(timer) = holder
+        .gpu_timer
+        .as_ref()
+        .filter(|_| app.profiler.is_enabled())
+    {
+        timer.resolve(&mut encoder);
+    }
+    render_hud(app, &mut encoder, &view);
     app.queue.submit([encoder.finish()]);
+    check_screenshot(app, &output.texture);
     output.present();
+    end_profiled_frame(app);
 }
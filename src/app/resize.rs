@@ -1,24 +1,61 @@
 use crate::app::App;
 use tracing::{error, info, info_span};
+use winit::dpi::PhysicalSize;
 use winit::event::WindowEvent;
 
+fn reconfigure(app: &mut App, new_size: PhysicalSize<u32>) {
+    let Some(win) = &mut app.window else {
+        error!("No window while resizing");
+        return;
+    };
+    info!("Resizing window to {}:{}", new_size.width, new_size.height);
+    win.needs_reconfigure = false;
+    win.window.request_redraw();
+    app.camera.on_resize(new_size);
+    win.surface_config.width = new_size.width;
+    win.surface_config.height = new_size.height;
+    win.surface.configure(&app.device, &win.surface_config);
+    win.registry.on_resize(&app.device, &win.surface_config);
+}
+
+/// Tracks whether the window can be seen, and configures the surface again when its size changes
+/// or when it was lost
 pub fn check_resize(app: &mut App, event: &WindowEvent) {
-    if let WindowEvent::Resized(new_size) = event {
-        let _span = info_span!("resize").entered();
-        if new_size.width == 0 || new_size.height == 0 {
-            info!("Zero size: don't resize");
-            return;
+    let Some(win) = &mut app.window else {
+        return;
+    };
+    let was_hidden = win.is_hidden();
+    match event {
+        WindowEvent::Resized(new_size) => {
+            let _span = info_span!("resize").entered();
+            win.minimized = new_size.width == 0 || new_size.height == 0;
+            if win.minimized {
+                info!("Zero size: pausing until the window is restored");
+            } else {
+                reconfigure(app, *new_size);
+            }
         }
-        if let Some(win) = &mut app.window {
-            info!("Resizing window to {}:{}", new_size.width, new_size.height);
-            win.window.request_redraw();
-            app.camera.on_resize(*new_size);
-            win.surface_config.width = new_size.width;
-            win.surface_config.height = new_size.height;
-            win.surface.configure(&app.device, &win.surface_config);
-            win.registry.on_resize(&app.device, &win.surface_config);
-        } else {
-            error!("No window while resizing");
+        WindowEvent::Occluded(occluded) => {
+            win.occluded = *occluded;
+            if *occluded {
+                info!("Window occluded, pausing");
+            } else {
+                info!("Window visible again");
+            }
         }
+        WindowEvent::RedrawRequested if win.needs_reconfigure && !win.minimized => {
+            let _span = info_span!("resize").entered();
+            let size = win.window.inner_size();
+            reconfigure(app, size);
+        }
+        _ => {}
+    }
+    // The hidden time isn't played at once when resuming
+    if let Some(win) = &app.window
+        && was_hidden
+        && !win.is_hidden()
+    {
+        app.clock.skip_elapsed();
+        win.window.request_redraw();
     }
 }
@@ -1,41 +1,63 @@
-use crate::math::Transform;
-use crate::utils::{Length, binary_search_interval};
+use crate::math::Frustum;
+use crate::profiler::Profiler;
+use crate::utils::binary_search_interval;
+use crate::world::lod::{FrameBudget, LodDecision, LodInput};
+use crate::world::picking::{Pick, Ray};
 use crate::world::primitives::camera::Camera;
+use crate::world::scene_graph::SceneGraph;
 use crate::world::world::{WorldSettings, Worlds};
 use crate::world::world_builder::{WorldBuilderFinalizationValue, WorldId};
 use crate::{
     render_registry::{alloc::BufferAllocator, registry::PipelinesRegistry},
     world::{world::World, world_builder::WorldsBuilder},
 };
-use tracing::{info, info_span};
+use tracing::{info, info_span, warn};
 
 use super::camera::ManualCamera;
-const BUCKET_FADE_THRESOLD: f32 = 5.;
-const VIEW_MAX_DISTANCE: f32 = 100.;
-
-fn view_parameters(cam_pos: Transform, world_extents: Option<Transform>) -> (bool, f32) {
-    match world_extents {
-        None => (true, 1.),
-        Some(extents) => {
-            // TODO passer dans l'espace de la camera ?
-            let size_squared = 0.0f32
-                .max(extents.x().length_squared())
-                .max(extents.y().length_squared())
-                .max(extents.z().length_squared());
-            let dist = ((cam_pos.trans() - extents.trans()).length_squared() / size_squared).sqrt();
-            (
-                dist < VIEW_MAX_DISTANCE,
-                BUCKET_FADE_THRESOLD / dist.max(BUCKET_FADE_THRESOLD),
-            )
-        }
-    }
+
+/// A camera index of the scene seen in a viewport
+#[derive(Clone, Copy, Debug)]
+pub struct ViewCamera {
+    pub index: isize,
+    pub aspect_ratio: f32,
+}
+
+/// The state of the frame a scene is updated for
+#[derive(Clone, Copy)]
+pub struct SceneFrame<'a> {
+    pub time: f32,
+    pub manual_camera: &'a ManualCamera,
+    /// The cameras of the viewports
+    pub cameras: &'a [ViewCamera],
+    pub budget: FrameBudget,
+}
+
+/// How many worlds were drawn in the last frame, and why the others weren't
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CullStats {
+    pub shown: usize,
+    /// Outside of the view of every camera
+    pub culled: usize,
+    /// Hidden by their LOD policy
+    pub too_far: usize,
 }
 
 pub struct Scene {
     worlds: Vec<World>,
     ticks: Vec<f32>,
+    /// The decisions of the last update, the worlds not updated yet are hidden
+    lods: Vec<LodDecision>,
+    /// Whether the worlds were outside of every camera frustum in the last update
+    culled: Vec<bool>,
+    /// Number of frames each world was culled in
+    culled_frames: Vec<u64>,
+    cull_stats: CullStats,
     id_by_layer: Vec<Vec<WorldId>>,
     pub allocs: Vec<BufferAllocator>,
+    /// The world holding the manual camera, its cameras are numbered last
+    manual_camera_world: usize,
+    /// The worlds in the order their cameras are numbered
+    camera_worlds: Vec<usize>,
     camera_offsets: Vec<usize>,
 }
 impl Scene {
@@ -46,34 +68,134 @@ impl Scene {
         let mut worlds_builder = builder_fun();
 
         let WorldBuilderFinalizationValue {
-            camera_offsets,
             worlds,
             buffer_allocations,
             id_by_layer,
         } = worlds_builder.finalize();
 
-        Scene {
+        let mut scene = Scene {
             ticks: vec![1.; worlds.len()],
+            lods: vec![LodDecision::HIDDEN; worlds.len()],
+            culled: vec![false; worlds.len()],
+            culled_frames: vec![0; worlds.len()],
+            cull_stats: CullStats::default(),
+            manual_camera_world: worlds.len() - 1,
             worlds,
             allocs: buffer_allocations,
             id_by_layer,
-            camera_offsets,
+            camera_worlds: Vec::new(),
+            camera_offsets: Vec::new(),
+        };
+        scene.update_camera_offsets();
+        scene
+    }
+    fn update_camera_offsets(&mut self) {
+        let manual = self.manual_camera_world;
+        self.camera_worlds = (0..self.worlds.len())
+            .filter(|&i| i != manual)
+            .chain([manual])
+            .collect();
+        self.camera_offsets = vec![0; self.worlds.len() + 1];
+        for (k, &i) in self.camera_worlds.iter().enumerate() {
+            self.camera_offsets[k + 1] =
+                self.camera_offsets[k] + self.worlds[i].stores.nb_cameras();
         }
     }
-    fn get_cam(&self, id: isize) -> Camera {
-        let (world_id, cam_idx) = binary_search_interval(
+    /// Builds more worlds while running, with ids following the existing ones.
+    /// Their GPU buffers have to be allocated by the registry
+    pub fn add_worlds(
+        &mut self,
+        build: impl FnOnce(WorldsBuilder) -> WorldsBuilder,
+    ) -> Vec<WorldId> {
+        let _span = info_span!("add_worlds").entered();
+        let WorldBuilderFinalizationValue {
+            worlds,
+            buffer_allocations,
+            id_by_layer,
+        } = build(WorldsBuilder::continuing(self.worlds.len())).finalize_added();
+        info!("Adding {} worlds to the scene", worlds.len());
+
+        self.ticks.extend(worlds.iter().map(|_| 1.));
+        self.lods.extend(worlds.iter().map(|_| LodDecision::HIDDEN));
+        self.culled.extend(worlds.iter().map(|_| false));
+        self.culled_frames.extend(worlds.iter().map(|_| 0));
+        self.worlds.extend(worlds);
+        self.allocs.extend(buffer_allocations);
+        if self.id_by_layer.len() < id_by_layer.len() {
+            self.id_by_layer.resize_with(id_by_layer.len(), Vec::new);
+        }
+        let mut added = Vec::new();
+        for (layer, ids) in id_by_layer.into_iter().enumerate() {
+            added.extend(&ids);
+            self.id_by_layer[layer].extend(ids);
+        }
+        self.update_camera_offsets();
+        added
+    }
+    /// Replaces a world by an empty one, so the other ids stay valid, the refs to it mustn't be used anymore.
+    /// The world with the manual camera can't be removed
+    pub fn remove_world(&mut self, id: WorldId) -> bool {
+        let i = id.get();
+        if i == self.manual_camera_world || i >= self.worlds.len() {
+            warn!("Can't remove the world {i}");
+            return false;
+        }
+        info!("Removing the world {i} from the scene");
+        for ids in &mut self.id_by_layer {
+            ids.retain(|&other| other != id);
+        }
+        self.worlds[i] = World::new();
+        self.allocs[i] = BufferAllocator::new();
+        self.lods[i] = LodDecision::HIDDEN;
+        self.culled[i] = false;
+        self.culled_frames[i] = 0;
+        self.update_camera_offsets();
+        true
+    }
+    /// To attach, detach or re-parent the nodes of a world while running
+    pub fn scene_graph_mut(&mut self, id: WorldId) -> Option<&mut SceneGraph> {
+        self.worlds.get_mut(id.get()).map(|w| &mut w.scene_graph)
+    }
+    /// The counts of the last update
+    pub fn cull_stats(&self) -> CullStats {
+        self.cull_stats
+    }
+    /// The number of frames the world was outside of every camera frustum
+    pub fn culled_frames(&self, id: WorldId) -> u64 {
+        self.culled_frames.get(id.get()).copied().unwrap_or(0)
+    }
+    fn is_shown(&self, i: usize) -> bool {
+        self.lods[i].visible && !self.culled[i]
+    }
+    pub fn get_cam(&self, id: isize) -> Camera {
+        let (k, cam_idx) = binary_search_interval(
             &self.camera_offsets,
             id.rem_euclid(*self.camera_offsets.last().unwrap() as isize) as usize,
         );
-        self.worlds[world_id].get_cam(cam_idx)
+        self.worlds[self.camera_worlds[k]].get_cam(cam_idx)
+    }
+    /// The closest instance hit by the ray among the shown worlds
+    pub fn pick(&self, ray: Ray) -> Option<Pick> {
+        self.worlds
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| self.is_shown(*i))
+            .filter_map(|(i, w)| w.pick(i, &self.allocs[i], ray))
+            .min_by(|a, b| a.distance.total_cmp(&b.distance))
     }
     pub fn update(
         &mut self,
         registry: &mut PipelinesRegistry,
         queue: &wgpu::Queue,
-        time: f32,
-        manu_cam: &ManualCamera,
+        frame: SceneFrame,
+        profiler: &mut Profiler,
     ) {
+        let SceneFrame {
+            time,
+            manual_camera: manu_cam,
+            cameras,
+            budget,
+        } = frame;
         // let _span = info_span!("update_scene").entered();
         // info!("Updating scene");
 
@@ -86,49 +208,142 @@ impl Scene {
                 cam_settings: manu_cam.cam,
             },
         };
-        let wcam = self.get_cam(manu_cam.current_cam_idx);
+        let wcams = cameras
+            .iter()
+            .map(|c| self.get_cam(c.index))
+            .collect::<Vec<_>>();
+        let frustums = wcams
+            .iter()
+            .zip(cameras)
+            .map(|(wcam, c)| Frustum::from_matrix(wcam.matrix(c.aspect_ratio)))
+            .collect::<Vec<_>>();
+        let mut stats = CullStats::default();
         for ids in &self.id_by_layer {
             for id in ids {
                 let i = id.get();
                 let w = &self.worlds[i];
                 worlds.world = w;
 
-                let world_extents = w.view_bounding_box.as_ref().map(|v| v.update(&worlds));
-                let (show, tick_add) = view_parameters(wcam.pos, world_extents);
+                let extents = w.view_bounding_box.as_ref().map(|v| v.update(&worlds));
+                let previous = self.lods[i];
+                let lod = wcams
+                    .iter()
+                    .map(|wcam| {
+                        w.lod_policy.decide(&LodInput {
+                            camera: wcam.pos,
+                            extents,
+                            budget,
+                            previous,
+                        })
+                    })
+                    .reduce(LodDecision::merge)
+                    .unwrap_or(LodDecision {
+                        update_rate: 0.,
+                        ..LodDecision::HIDDEN
+                    });
+                let culled = lod.visible
+                    && extents.is_some_and(|ext| !frustums.iter().any(|f| f.intersects_box(ext)));
+                let shown = lod.visible && !culled;
+                if culled {
+                    stats.culled += 1;
+                    self.culled_frames[i] += 1;
+                } else if shown {
+                    stats.shown += 1;
+                } else {
+                    stats.too_far += 1;
+                }
 
-                self.ticks[i] += tick_add;
+                self.ticks[i] += lod.update_rate;
                 if self.ticks[i] >= 1. {
                     self.ticks[i] = 0.;
-                    w.update_registers(&worlds);
-                    let mut stores_views = registry.store_bindings[i].views(queue);
-                    let stores = stores_views.each_mut().map(|v| {
-                        v.as_deref_mut()
-                            .map(|view| bytemuck::cast_slice_mut(view))
-                            .unwrap_or(&mut [])
-                    });
-                    w.write_stores(stores);
+                    profiler.time(
+                        || format!("update_registers world {i}"),
+                        || w.update_registers(&worlds),
+                    );
+                    profiler.time(
+                        || format!("write_stores world {i}"),
+                        || w.write_stores(&mut registry.store_bindings[i], queue),
+                    );
                 }
-                if show {
-                    let mut instance_bufs_views = registry.views(queue, i);
-                    let instance_bufs = instance_bufs_views.each_mut().map(|r| {
-                        r.each_mut().map(|view_opt| {
-                            view_opt
-                                .as_mut()
-                                .map(|view| bytemuck::cast_slice_mut(&mut *view))
-                                .unwrap_or(&mut [])
-                        })
-                    });
-                    w.redraw(instance_bufs);
+                if shown {
+                    profiler.time(
+                        || format!("redraw world {i}"),
+                        || {
+                            let mut instance_bufs_views = registry.views(queue, i);
+                            let instance_bufs = instance_bufs_views.each_mut().map(|r| {
+                                r.each_mut().map(|view_opt| {
+                                    view_opt
+                                        .as_mut()
+                                        .map(|view| bytemuck::cast_slice_mut(&mut *view))
+                                        .unwrap_or(&mut [])
+                                })
+                            });
+                            w.redraw(instance_bufs);
+                        },
+                    );
                 }
-                registry.pipes[i].activated = show;
+                registry.pipes[i].activated = shown;
+                registry.pipes[i].detail = lod.detail;
+                self.lods[i] = lod;
+                self.culled[i] = culled;
             }
         }
-        let wcam = self.get_cam(manu_cam.current_cam_idx);
-        registry
-            .base_bindings
-            .set_camera(queue, wcam.matrix(manu_cam.aspect_ratio()));
-        registry
-            .base_bindings
-            .set_camera_transform(queue, wcam.pos.to_mat4());
+        self.cull_stats = stats;
+        for (i, camera) in cameras.iter().enumerate() {
+            let wcam = self.get_cam(camera.index);
+            let bindings = registry.bindings(i);
+            bindings.set_camera(queue, wcam.matrix(camera.aspect_ratio));
+            bindings.set_camera_transform(queue, wcam.pos.to_mat4());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_cameras(worlds: &mut WorldsBuilder, layer: usize, nb_cameras: usize) {
+        worlds.add_world_with(layer, |w| {
+            for _ in 0..nb_cameras {
+                w.push(|_: &Worlds| Camera::default());
+            }
+        });
+    }
+
+    fn ids(ids: &[WorldId]) -> Vec<usize> {
+        ids.iter().map(|id| id.get()).collect()
+    }
+    fn layers(scene: &Scene) -> Vec<Vec<usize>> {
+        scene.id_by_layer.iter().map(|l| ids(l)).collect()
+    }
+
+    #[test]
+    fn add_and_remove_worlds() {
+        let mut scene = Scene::new(&mut || {
+            let mut worlds = WorldsBuilder::default();
+            with_cameras(&mut worlds, 0, 2);
+            with_cameras(&mut worlds, 1, 1);
+            worlds
+        });
+        // The second world also holds the manual camera
+        assert_eq!(scene.manual_camera_world, 1);
+        assert_eq!(scene.camera_offsets, [0, 2, 4]);
+
+        let added = scene.add_worlds(|mut worlds| {
+            with_cameras(&mut worlds, 1, 3);
+            worlds
+        });
+        assert_eq!(ids(&added), [2]);
+        assert_eq!(scene.camera_worlds, [0, 2, 1]);
+        assert_eq!(scene.camera_offsets, [0, 2, 5, 7]);
+        assert_eq!(layers(&scene), [vec![0], vec![1, 2]]);
+
+        let (first, manual) = (scene.id_by_layer[0][0], scene.id_by_layer[1][0]);
+        assert!(!scene.remove_world(manual));
+        assert!(scene.remove_world(first));
+        assert_eq!(scene.worlds.len(), 3);
+        assert_eq!(scene.camera_worlds, [0, 2, 1]);
+        assert_eq!(scene.camera_offsets, [0, 0, 3, 5]);
+        assert_eq!(layers(&scene), [vec![], vec![1, 2]]);
     }
 }
@@ -1,31 +1,70 @@
 use std::path::{PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
-use tracing::{info, info_span};
+use tracing::{error, info, info_span};
 use crate::app::App;
+use crate::render_registry::offscreen::read_texture;
+use crate::utils::write_ppm;
 
-const OUTPUT_DIRECTORY: &'static str = "../out/screenshots";
+const OUTPUT_DIRECTORY: &str = "../out/screenshots";
 
 fn get_output_path() -> PathBuf {
     let mut path = PathBuf::from(OUTPUT_DIRECTORY);
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .expect("Failed to fetch system time");
-    let filename = format!("screenshot-{}.png", now.as_millis());
+    let filename = format!("screenshot-{}.ppm", now.as_millis());
     path.push(filename);
     path
 }
 
-pub fn capture_screenshot() {
+/// Whether the red and blue channels have to be swapped to get RGBA8, None if the format isn't handled
+fn swaps_red_blue(format: wgpu::TextureFormat) -> Option<bool> {
+    use wgpu::TextureFormat::*;
+    match format {
+        Rgba8Unorm | Rgba8UnormSrgb => Some(false),
+        Bgra8Unorm | Bgra8UnormSrgb => Some(true),
+        _ => None,
+    }
+}
+
+pub fn capture_screenshot(device: &wgpu::Device, queue: &wgpu::Queue, texture: &wgpu::Texture) {
     let _span = info_span!("screenshot").entered();
     let path = get_output_path();
 
     info!("Taking a screenshot saved at {path:?}");
 
+    if !texture.usage().contains(wgpu::TextureUsages::COPY_SRC) {
+        error!("The surface can't be copied, no screenshot taken");
+        return;
+    }
+    let format = texture.format();
+    let Some(swap) = swaps_red_blue(format) else {
+        error!("Unsupported surface format for screenshots: {format:?}");
+        return;
+    };
+    let mut pixels = read_texture(device, queue, texture);
+    if swap {
+        for px in pixels.chunks_exact_mut(4) {
+            px.swap(0, 2);
+        }
+    }
 
+    if let Some(dir) = path.parent()
+        && let Err(err) = std::fs::create_dir_all(dir)
+    {
+        error!("Failed to create the screenshot directory {dir:?}: {err}");
+        return;
+    }
+    let (width, height) = (texture.width(), texture.height());
+    match write_ppm(&path, width, height, &pixels) {
+        Ok(()) => info!("Saved a {width}x{height} screenshot at {path:?}"),
+        Err(err) => error!("Failed to write the screenshot at {path:?}: {err}"),
+    }
 }
 
-pub fn check_screenshot(app: &mut App) {
+/// Has to be called with the frame texture, before it gets presented
+pub fn check_screenshot(app: &App, frame: &wgpu::Texture) {
     if app.key_binds.window_utility.screenshot.is_active() {
-        capture_screenshot();
+        capture_screenshot(&app.device, &app.queue, frame);
     }
 }
@@ -0,0 +1,56 @@
+use crate::app::App;
+use crate::render_registry::shaders::{ShaderWatcher, Shaders};
+use std::time::{Duration, Instant};
+use tracing::{error, info, info_span};
+use winit::event::WindowEvent;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+pub struct ShaderReloader {
+    watcher: ShaderWatcher,
+    last_poll: Instant,
+}
+impl ShaderReloader {
+    pub fn new() -> Self {
+        Self {
+            watcher: ShaderWatcher::new(),
+            // Polls on the first frame, to use the sources on disk
+            last_poll: Instant::now() - POLL_INTERVAL,
+        }
+    }
+    /// Reloads the sources from disk on the next frame, even if they didn't change
+    pub fn invalidate(&mut self) {
+        self.watcher = ShaderWatcher::new();
+        self.last_poll = Instant::now() - POLL_INTERVAL;
+    }
+}
+
+pub fn check_shader_reload(app: &mut App, event: &WindowEvent) {
+    if !matches!(event, WindowEvent::RedrawRequested) {
+        return;
+    }
+    let (Some(reloader), Some(holder)) = (&mut app.shader_reloader, &mut app.window) else {
+        return;
+    };
+    if reloader.last_poll.elapsed() < POLL_INTERVAL {
+        return;
+    }
+    reloader.last_poll = Instant::now();
+    if !reloader.watcher.poll() {
+        return;
+    }
+    let _span = info_span!("shader_reload").entered();
+    info!("Reloading the shaders from disk");
+    let sources = match reloader.watcher.read_sources() {
+        Ok(sources) => sources,
+        Err(err) => {
+            error!("Failed to read the shaders: {err}");
+            return;
+        }
+    };
+    let result = Shaders::from_source(&app.device, sources)
+        .and_then(|shaders| holder.registry.reload_shaders(&app.device, shaders));
+    if let Err(err) = result {
+        error!("Invalid shaders, keeping the previous ones: {err}");
+    }
+}
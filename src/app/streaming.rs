@@ -0,0 +1,45 @@
+use crate::app::App;
+use crate::world::world_builder::{WorldId, WorldsBuilder};
+
+/// Called before each scene update, to stream worlds in and out
+pub type UpdateHook = Box<dyn FnMut(&mut App)>;
+
+impl App {
+    /// Builds worlds and adds them to the running scene, with their GPU buffers.
+    /// `HeadlessRenderer` has the same methods for its own scene
+    pub fn add_worlds(
+        &mut self,
+        build: impl FnOnce(WorldsBuilder) -> WorldsBuilder,
+    ) -> Vec<WorldId> {
+        let first = self.scene.allocs.len();
+        let ids = self.scene.add_worlds(build);
+        if let Some(holder) = &mut self.window {
+            for alloc in &self.scene.allocs[first..] {
+                holder
+                    .registry
+                    .add_world(&self.device, &holder.surface_config, alloc);
+            }
+        }
+        ids
+    }
+    /// Drops a world and frees its GPU buffers, the other worlds keep their ids
+    pub fn remove_world(&mut self, id: WorldId) -> bool {
+        if !self.scene.remove_world(id) {
+            return false;
+        }
+        if let Some(holder) = &mut self.window {
+            holder.registry.remove_world(&self.device, id.get());
+        }
+        true
+    }
+}
+
+pub fn run_update_hook(app: &mut App) {
+    if let Some(mut hook) = app.update_hook.take() {
+        hook(app);
+        // The hook may have replaced itself
+        if app.update_hook.is_none() {
+            app.update_hook = Some(hook);
+        }
+    }
+}
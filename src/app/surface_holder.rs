@@ -1,16 +1,32 @@
 use crate::app::App;
+use crate::render_registry::gpu_timer::GpuTimer;
+use crate::render_registry::hud::HudRenderer;
 use crate::render_registry::registry::PipelinesRegistry;
 use std::sync::Arc;
 use tracing::{info_span, warn};
-use winit::dpi::{LogicalSize, Size};
+use winit::dpi::{LogicalSize, PhysicalSize, Size};
 use winit::event_loop::ActiveEventLoop;
-use winit::window::Window;
+use winit::window::{Fullscreen, Window};
+
+/// How the window is opened, the defaults are chosen by the platform
+#[derive(Clone, Debug, Default)]
+pub struct WindowSettings {
+    pub size: Option<PhysicalSize<u32>>,
+    pub fullscreen: bool,
+}
 
 pub struct SurfaceHolder {
     pub window: Arc<Window>,
     pub surface: wgpu::Surface<'static>,
     pub surface_config: wgpu::SurfaceConfiguration,
     pub registry: PipelinesRegistry,
+    pub hud: HudRenderer,
+    pub gpu_timer: Option<GpuTimer>,
+    /// Zero-sized, nothing can be rendered
+    pub minimized: bool,
+    pub occluded: bool,
+    /// The surface was lost or outdated and has to be configured again
+    pub needs_reconfigure: bool,
 }
 
 fn cfg_window(win: &Window) {
@@ -22,11 +38,14 @@ impl SurfaceHolder {
     pub fn new(app: &App, event_loop: &ActiveEventLoop) -> Self {
         let _span = info_span!("create_win_surf");
 
-        let window = Arc::new(
-            event_loop
-                .create_window(Window::default_attributes())
-                .unwrap(),
-        );
+        let mut attributes = Window::default_attributes();
+        if let Some(size) = app.window_settings.size {
+            attributes = attributes.with_inner_size(size);
+        }
+        if app.window_settings.fullscreen {
+            attributes = attributes.with_fullscreen(Some(Fullscreen::Borderless(None)));
+        }
+        let window = Arc::new(event_loop.create_window(attributes).unwrap());
         cfg_window(&window);
 
         let surface = app.instance.create_surface(window.clone()).unwrap();
@@ -43,9 +62,12 @@ impl SurfaceHolder {
         let size = window.inner_size();
         let surface_config = wgpu::SurfaceConfiguration {
             format,
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            width: size.width,
-            height: size.height,
+            // Copies are needed for the screenshots
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | (caps.usages & wgpu::TextureUsages::COPY_SRC),
+            // Configured again once the window gets a size
+            width: size.width.max(1),
+            height: size.height.max(1),
             alpha_mode: wgpu::CompositeAlphaMode::Auto,
             view_formats: vec![],
             desired_maximum_frame_latency: 2,
@@ -53,12 +75,23 @@ impl SurfaceHolder {
         };
 
         let registry = PipelinesRegistry::new(&app.device, &surface_config, &app.scene.allocs);
+        let hud = HudRenderer::new(&app.device, surface_config.format);
+        let gpu_timer = GpuTimer::new(&app.device, &app.queue);
 
         Self {
             window: window.clone(),
             registry,
+            hud,
+            gpu_timer,
+            minimized: size.width == 0 || size.height == 0,
+            occluded: false,
+            needs_reconfigure: false,
             surface_config,
             surface,
         }
     }
+    /// Updates and rendering are paused while the window can't be seen
+    pub fn is_hidden(&self) -> bool {
+        self.minimized || self.occluded
+    }
 }
@@ -1,56 +1,169 @@
+use crate::app::keybinds::KeyBinds;
+use crate::app::scene::SceneFrame;
+use crate::app::streaming::run_update_hook;
+use crate::world::lod::FrameBudget;
 use crate::{app::App, settings::perf_level};
 use std::time::Instant;
 use tracing::{info, info_span};
 use winit::event::WindowEvent;
 
+const MIN_SPEED: f32 = 1. / 64.;
+const MAX_SPEED: f32 = 64.;
+
+/// Keeps the scene time, which can be paused, slowed down, reversed or stepped
 pub struct Clock {
-    startup: Instant,
     last_render: Instant,
+    last_delta: f32,
     min_delta: f32,
+    time: f32,
+    speed: f32,
+    paused: bool,
+}
+fn target_fps() -> f32 {
+    perf_level!(
+        30.0
+        => VeryHighPerf
+        60.0
+    )
 }
+
 impl Clock {
     pub fn new() -> Self {
-        let target_fps = perf_level!(
-            30.0
-            => VeryHighPerf
-            60.0
-        );
-
         Self {
-            startup: Instant::now(),
             last_render: Instant::now(),
-            min_delta: 1. / target_fps,
+            last_delta: 0.,
+            min_delta: 1. / target_fps(),
+            time: 0.,
+            speed: 1.,
+            paused: false,
         }
     }
+    /// The target fps follows the perf level
+    pub fn update_target_fps(&mut self) {
+        self.min_delta = 1. / target_fps();
+    }
+    /// Forgets the real time elapsed since the last update
+    pub fn skip_elapsed(&mut self) {
+        self.last_render = Instant::now();
+    }
     pub fn should_update(&self) -> bool {
         self.last_render.elapsed().as_secs_f32() > self.min_delta
     }
+    /// Real time between the two last updates
+    pub fn last_delta(&self) -> f32 {
+        self.last_delta
+    }
+    pub fn target_fps(&self) -> f32 {
+        1. / self.min_delta
+    }
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+    fn toggle_pause(&mut self) {
+        self.paused ^= true;
+        if self.paused {
+            info!("Pausing time at {}", self.time);
+        } else {
+            info!("Resuming time at {}", self.time);
+        }
+    }
+    fn set_speed(&mut self, speed: f32) {
+        self.speed = speed.abs().clamp(MIN_SPEED, MAX_SPEED).copysign(speed);
+        info!("Setting time speed to {}", self.speed);
+    }
+    /// Pauses the time and moves it by a number of frames at the target fps
+    fn step(&mut self, frames: f32) {
+        self.paused = true;
+        self.time += frames * self.min_delta;
+        info!("Stepping time to {} ({:+} frame)", self.time, frames);
+    }
+    fn process_binds(&mut self, binds: &KeyBinds) {
+        let binds = &binds.time_control;
+        if binds.toggle_pause.is_active() {
+            self.toggle_pause();
+        }
+        if binds.speed_up.is_active() {
+            self.set_speed(self.speed * 2.);
+        }
+        if binds.slow_down.is_active() {
+            self.set_speed(self.speed / 2.);
+        }
+        if binds.reverse.is_active() {
+            self.set_speed(-self.speed);
+        }
+        if binds.reset_speed.is_active() {
+            self.set_speed(1.);
+        }
+        if binds.step_forward.is_active() {
+            self.step(1.);
+        }
+        if binds.step_backward.is_active() {
+            self.step(-1.);
+        }
+    }
+    /// Advances the scene time by a real time delta, returns the new scene time
+    pub fn advance(&mut self, dt: f32, binds: &KeyBinds) -> f32 {
+        self.process_binds(binds);
+        if !self.paused {
+            self.time += dt * self.speed;
+        }
+        self.time
+    }
 }
 
 pub fn check_update(app: &mut App, event: &WindowEvent) {
     if !matches!(event, WindowEvent::RedrawRequested) {
         return;
     }
+    if app.window.as_ref().is_some_and(|win| win.is_hidden()) {
+        return;
+    }
     let _span = info_span!("update").entered();
     let now = Instant::now();
     let delta = now - app.clock.last_render;
-    let time = (now - app.clock.startup).as_secs_f32();
+    let time = app.clock.advance(delta.as_secs_f32(), &app.key_binds);
     if app.key_binds.window_debug.show_fps.is_active() {
         info!(
-            "delta={}ms, fps={}/{}, time={}",
+            "delta={}ms, fps={}/{}, time={}, speed={}{}",
             delta.as_millis(),
             1. / delta.as_secs_f32(),
             1. / app.clock.min_delta,
-            time
+            time,
+            app.clock.speed,
+            if app.clock.paused { " (paused)" } else { "" }
         );
     }
     app.clock.last_render = now;
+    app.clock.last_delta = delta.as_secs_f32();
+    run_update_hook(app);
 
     if let Some(holder) = &mut app.window {
         app.camera
             .update(delta.as_secs_f32(), &holder.window, &app.key_binds);
-        holder.registry.base_bindings.set_time(&app.queue, time); //, app.clock.loop_time);
-        app.scene
-            .update(&mut holder.registry, &app.queue, time, &app.camera);
+        app.camera_recorder
+            .update(&mut app.camera, time, &app.key_binds);
+        holder.registry.set_time(&app.queue, time); //, app.clock.loop_time);
+        let (width, height) = (holder.surface_config.width, holder.surface_config.height);
+        let cameras = app.viewports.layout().cameras(&app.camera, width, height);
+        app.scene.update(
+            &mut holder.registry,
+            &app.queue,
+            SceneFrame {
+                time,
+                manual_camera: &app.camera,
+                cameras: &cameras,
+                budget: FrameBudget {
+                    target: app.clock.min_delta,
+                    last: delta.as_secs_f32(),
+                },
+            },
+            &mut app.profiler,
+        );
     }
 }
@@ -0,0 +1,177 @@
+use crate::app::App;
+use crate::app::camera::ManualCamera;
+use crate::app::scene::ViewCamera;
+use crate::render_registry::registry::ViewportRect;
+use tracing::info;
+use winit::event::WindowEvent;
+
+#[derive(Clone, Copy, Debug)]
+pub enum ViewportCamera {
+    /// The camera selected with the camera change binds
+    Current,
+    /// A fixed camera index, -1 being the manual camera
+    Fixed(isize),
+}
+
+/// A part of the window, in fractions of its size from the top left corner
+#[derive(Clone, Copy, Debug)]
+pub struct Viewport {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub camera: ViewportCamera,
+}
+impl Viewport {
+    pub const FULL: Self = Self {
+        x: 0.,
+        y: 0.,
+        width: 1.,
+        height: 1.,
+        camera: ViewportCamera::Current,
+    };
+}
+
+#[derive(Clone, Debug)]
+pub struct ViewportLayout {
+    pub name: &'static str,
+    pub viewports: Vec<Viewport>,
+}
+impl ViewportLayout {
+    pub fn single() -> Self {
+        Self {
+            name: "single",
+            viewports: vec![Viewport::FULL],
+        }
+    }
+    /// The manual camera on the left, the current one on the right
+    pub fn side_by_side() -> Self {
+        Self {
+            name: "side by side",
+            viewports: vec![
+                Viewport {
+                    width: 0.5,
+                    camera: ViewportCamera::Fixed(-1),
+                    ..Viewport::FULL
+                },
+                Viewport {
+                    x: 0.5,
+                    width: 0.5,
+                    ..Viewport::FULL
+                },
+            ],
+        }
+    }
+    /// The current camera and the three first cameras of the scene
+    pub fn quad() -> Self {
+        let quarter = |x, y, camera| Viewport {
+            x,
+            y,
+            width: 0.5,
+            height: 0.5,
+            camera,
+        };
+        Self {
+            name: "quad",
+            viewports: vec![
+                quarter(0., 0., ViewportCamera::Current),
+                quarter(0.5, 0., ViewportCamera::Fixed(0)),
+                quarter(0., 0.5, ViewportCamera::Fixed(1)),
+                quarter(0.5, 0.5, ViewportCamera::Fixed(2)),
+            ],
+        }
+    }
+    pub fn presets() -> Vec<Self> {
+        vec![Self::single(), Self::side_by_side(), Self::quad()]
+    }
+    /// In pixels, adjacent viewports share their edges and stay inside the surface
+    pub fn rects(&self, width: u32, height: u32) -> Vec<ViewportRect> {
+        let (width, height) = (width as f32, height as f32);
+        // The start and the length of a side, at least one pixel
+        let edges = |from: f32, len: f32, size: f32| {
+            let start = (from * size).round().min(size - 1.).max(0.);
+            let end = ((from + len) * size).round().min(size);
+            (start, (end - start).max(1.))
+        };
+        self.viewports
+            .iter()
+            .map(|v| {
+                let (x, width) = edges(v.x, v.width, width);
+                let (y, height) = edges(v.y, v.height, height);
+                ViewportRect {
+                    x,
+                    y,
+                    width,
+                    height,
+                }
+            })
+            .collect()
+    }
+    pub fn cameras(&self, manual: &ManualCamera, width: u32, height: u32) -> Vec<ViewCamera> {
+        self.viewports
+            .iter()
+            .zip(self.rects(width, height))
+            .map(|(v, rect)| ViewCamera {
+                index: match v.camera {
+                    ViewportCamera::Current => manual.current_cam_idx,
+                    ViewportCamera::Fixed(index) => index,
+                },
+                aspect_ratio: rect.aspect_ratio(),
+            })
+            .collect()
+    }
+}
+
+/// The selectable layouts, cycled with a bind
+pub struct Viewports {
+    pub layouts: Vec<ViewportLayout>,
+    pub current: usize,
+}
+impl Viewports {
+    pub fn new() -> Self {
+        Self {
+            layouts: ViewportLayout::presets(),
+            current: 0,
+        }
+    }
+    pub fn layout(&self) -> &ViewportLayout {
+        &self.layouts[self.current]
+    }
+}
+
+pub fn check_viewports(app: &mut App, event: &WindowEvent) {
+    if !matches!(event, WindowEvent::RedrawRequested) {
+        return;
+    }
+    if app.key_binds.window_utility.next_layout.is_active() {
+        let viewports = &mut app.viewports;
+        viewports.current = (viewports.current + 1) % viewports.layouts.len();
+        info!("Changing the viewport layout to {}", viewports.layout().name);
+    }
+    if let Some(holder) = &mut app.window {
+        holder
+            .registry
+            .set_viewport_count(&app.device, app.viewports.layout().viewports.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rects_inside_surface() {
+        for layout in ViewportLayout::presets() {
+            for (width, height) in [(1281, 721), (1280, 720), (1, 1), (2, 3)] {
+                for rect in layout.rects(width, height) {
+                    assert!(rect.width >= 1. && rect.height >= 1.);
+                    assert!(rect.x + rect.width <= width as f32);
+                    assert!(rect.y + rect.height <= height as f32);
+                }
+            }
+        }
+        let rects = ViewportLayout::side_by_side().rects(1281, 721);
+        assert_eq!(rects[0].x + rects[0].width, rects[1].x);
+        assert_eq!(rects[1].x + rects[1].width, 1281.);
+    }
+}
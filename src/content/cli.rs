@@ -0,0 +1,132 @@
+use lib_space_animation::settings::PerfLevel;
+use std::path::PathBuf;
+use winit::dpi::PhysicalSize;
+
+pub const USAGE: &str = "\
+Usage: space_animation [OPTIONS]
+
+Options:
+    --scene <NAME>        Scene to show, see --list-scenes
+    --list-scenes         Print the registered scenes and exit
+    --scene-file <PATH>   Scene file to show instead, read again on reload
+    --size <W>x<H>        Window size, or output size when headless
+    --fullscreen          Open a borderless fullscreen window
+    --perf <LEVEL>        VeryHighPerf, HighPerf, AveragePerf, HighDetails or VeryHighDetails
+                          (defaults to SPACE_ANIMATION_PERF_LEVEL, then HighPerf)
+    --camera <INDEX>      Initial camera index
+    --log <FILTER>        Log filter, like `info,wgpu_core=warn`
+    --screenshot <PATH>   Render a single frame without window into a ppm file
+    --time <SECONDS>      Time of the screenshot, or start of the frame sequence
+    --frames <DIR>        Render a frame sequence without window into a directory
+    --fps <FPS>           Frame rate of the sequence
+    --duration <SECONDS>  Duration of the sequence
+    --gif                 Also assemble the sequence into a gif
+    -h, --help            Print this help
+";
+
+/// What to do with the scene
+pub enum Output {
+    Window,
+    Screenshot(PathBuf),
+    Frames(PathBuf),
+}
+
+pub struct Cli {
+    pub scene: Option<String>,
+    pub scene_file: Option<PathBuf>,
+    pub list_scenes: bool,
+    pub size: Option<PhysicalSize<u32>>,
+    pub fullscreen: bool,
+    pub perf_level: Option<PerfLevel>,
+    pub camera_index: isize,
+    pub log_filter: Option<String>,
+    pub output: Output,
+    pub time: Option<f32>,
+    pub fps: Option<f32>,
+    pub duration: Option<f32>,
+    pub gif: bool,
+    pub help: bool,
+}
+
+fn parse_size(value: &str) -> Result<PhysicalSize<u32>, String> {
+    let (w, h) = value
+        .split_once('x')
+        .ok_or_else(|| format!("invalid size `{value}`, expected <W>x<H>"))?;
+    let parse = |v: &str| {
+        v.parse::<u32>()
+            .ok()
+            .filter(|&v| v > 0)
+            .ok_or_else(|| format!("invalid size `{value}`, expected <W>x<H>"))
+    };
+    Ok(PhysicalSize::new(parse(w)?, parse(h)?))
+}
+
+fn parse_number<T: std::str::FromStr>(option: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value `{value}` for {option}"))
+}
+
+impl Cli {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut cli = Self {
+            scene: None,
+            scene_file: None,
+            list_scenes: false,
+            size: None,
+            fullscreen: false,
+            perf_level: None,
+            camera_index: 0,
+            log_filter: None,
+            output: Output::Window,
+            time: None,
+            fps: None,
+            duration: None,
+            gif: false,
+            help: false,
+        };
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("missing value for {arg}"))
+            };
+            match arg.as_str() {
+                "--scene" => cli.scene = Some(value()?),
+                "--scene-file" => cli.scene_file = Some(value()?.into()),
+                "--list-scenes" => cli.list_scenes = true,
+                "--size" => cli.size = Some(parse_size(&value()?)?),
+                "--fullscreen" => cli.fullscreen = true,
+                "--perf" => cli.perf_level = Some(value()?.parse()?),
+                "--camera" => cli.camera_index = parse_number(&arg, &value()?)?,
+                "--log" => cli.log_filter = Some(value()?),
+                "--screenshot" => cli.output = Output::Screenshot(value()?.into()),
+                "--frames" => cli.output = Output::Frames(value()?.into()),
+                "--time" => cli.time = Some(parse_number(&arg, &value()?)?),
+                "--fps" => cli.fps = Some(parse_number(&arg, &value()?)?),
+                "--duration" => cli.duration = Some(parse_number(&arg, &value()?)?),
+                "--gif" => cli.gif = true,
+                "-h" | "--help" => cli.help = true,
+                _ => return Err(format!("unknown argument `{arg}`")),
+            }
+        }
+        // Options only read by the headless modes, with the modes reading them
+        let headless_options = [
+            ("--time", cli.time.is_some(), "--screenshot or --frames"),
+            ("--fps", cli.fps.is_some(), "--frames"),
+            ("--duration", cli.duration.is_some(), "--frames"),
+            ("--gif", cli.gif, "--frames"),
+        ];
+        for (option, given, modes) in headless_options {
+            let read = match cli.output {
+                Output::Window => false,
+                Output::Screenshot(_) => option == "--time",
+                Output::Frames(_) => true,
+            };
+            if given && !read {
+                return Err(format!("{option} requires {modes}"));
+            }
+        }
+        Ok(cli)
+    }
+}
@@ -1,17 +1,155 @@
-macro_rules! import {
+mod cli;
+
+use cli::{Cli, Output, USAGE};
+use lib_space_animation::app::{App, ExportSettings, HeadlessRenderer, export_frames};
+use lib_space_animation::settings::{PerfLevel, set_perf_level};
+use lib_space_animation::utils::write_ppm;
+use lib_space_animation::world::scene_file::load_scene;
+use lib_space_animation::world::world_builder::WorldsBuilder;
+use lib_space_animation::logger;
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use tracing::{error, info};
+use winit::dpi::PhysicalSize;
+
+macro_rules! scenes {
     (
-        $name: ident
+        $($name: ident),*
+        $(,)?
     ) => {
-        mod $name;
-        pub use $name::build;
+        $(mod $name;)*
+
+        /// The registered scenes, the first one is the default
+        const SCENES: &[(&str, fn() -> WorldsBuilder)] = &[$(
+            (stringify!($name), $name::build),
+        )*];
+    };
+}
+
+// `tests` still uses the old `World` api, it can't be registered yet
+scenes!(
+    world_runner,
+);
+
+fn find_scene(name: Option<&str>) -> Result<fn() -> WorldsBuilder, String> {
+    let Some(name) = name else {
+        return Ok(SCENES[0].1);
     };
+    SCENES
+        .iter()
+        .find(|(scene, _)| *scene == name)
+        .map(|(_, build)| *build)
+        .ok_or_else(|| {
+            let names = SCENES.iter().map(|(scene, _)| *scene).collect::<Vec<_>>();
+            format!("unknown scene `{name}`, expected one of {}", names.join(", "))
+        })
 }
 
-// import!(tests);
+/// Reads the file again at each build, a scene with errors keeps the last valid one
+fn scene_file_builder(path: PathBuf) -> Result<impl FnMut() -> WorldsBuilder, String> {
+    let read = move || {
+        let src = fs::read_to_string(&path)
+            .map_err(|err| format!("failed to read the scene at {path:?}: {err}"))?;
+        load_scene(&src)
+            .map(|worlds| (src, worlds))
+            .map_err(|err| format!("{}:{err}", path.display()))
+    };
+    // The first build uses the scene checked at startup
+    let (mut last_valid, worlds) = read()?;
+    let mut first = Some(worlds);
+    Ok(move || {
+        if let Some(worlds) = first.take() {
+            return worlds;
+        }
+        match read() {
+            Ok((src, worlds)) => {
+                last_valid = src;
+                worlds
+            }
+            Err(err) => {
+                error!("{err}, keeping the previous scene");
+                load_scene(&last_valid).expect("the scene was already loaded")
+            }
+        }
+    })
+}
 
-import!(world_runner);
+fn run(cli: Cli) -> Result<(), String> {
+    let build: Box<dyn FnMut() -> WorldsBuilder> = match cli.scene_file {
+        Some(path) => Box::new(scene_file_builder(path)?),
+        None => Box::new(find_scene(cli.scene.as_deref())?),
+    };
+    match cli.log_filter {
+        Some(filter) => logger::init_logger_with(&filter),
+        None => logger::init_logger(),
+    }
+    if let Some(level) = cli.perf_level.or_else(PerfLevel::from_env) {
+        set_perf_level(level);
+    }
 
+    match cli.output {
+        Output::Window => {
+            let mut app = App::new(build).map_err(|err| err.to_string())?;
+            app.window_settings.size = cli.size;
+            app.window_settings.fullscreen = cli.fullscreen;
+            app.camera.current_cam_idx = cli.camera_index;
+            app.run().map_err(|err| err.to_string())?;
+        }
+        Output::Screenshot(path) => {
+            let size = cli.size.unwrap_or(PhysicalSize::new(1280, 720));
+            let mut renderer = HeadlessRenderer::new(build, size.width, size.height)
+                .map_err(|err| err.to_string())?;
+            renderer.camera.current_cam_idx = cli.camera_index;
+            let pixels = renderer.render(cli.time.unwrap_or(0.));
+            write_ppm(&path, size.width, size.height, &pixels)
+                .map_err(|err| format!("failed to write the screenshot at {path:?}: {err}"))?;
+            info!("Saved a {}x{} screenshot at {path:?}", size.width, size.height);
+        }
+        Output::Frames(output_directory) => {
+            let mut settings = ExportSettings {
+                camera_index: cli.camera_index,
+                output_directory,
+                gif: cli.gif,
+                ..Default::default()
+            };
+            if let Some(size) = cli.size {
+                (settings.width, settings.height) = (size.width, size.height);
+            }
+            settings.start = cli.time.unwrap_or(settings.start);
+            settings.fps = cli.fps.unwrap_or(settings.fps);
+            settings.duration = cli.duration.unwrap_or(settings.duration);
+            export_frames(build, &settings)
+                .map_err(|err| format!("failed to export the frames: {err}"))?;
+        }
+    }
+    Ok(())
+}
 
-fn main() {
-    lib_space_animation::run(build)
+fn main() -> ExitCode {
+    let cli = match Cli::parse(std::env::args().skip(1)) {
+        Ok(cli) => cli,
+        Err(err) => {
+            eprintln!("error: {err}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    if cli.help {
+        print!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+    if cli.list_scenes {
+        for (name, _) in SCENES {
+            println!("{name}");
+        }
+        return ExitCode::SUCCESS;
+    }
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            error!("{err}");
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -12,6 +12,7 @@ use lib_space_animation::{
     utils::{Length, Zero},
     world::{
         primitives::color::Color,
+        scene_graph::Parent,
         variators::{references::Ref, variator::Variator},
         visuals::{Pipe, Sphere},
         world_builder::WorldsBuilder,
@@ -54,13 +55,13 @@ pub fn build() -> WorldsBuilder {
 
     let local_sphere = world.push(Transform::from_scalef(0.2, 0.2, 0.2));
 
-    let transforms = points
+    let nodes = points
         .iter()
         .copied()
         .map(|pos: Vec3| {
             let a = *rng.random::<Dir>() * 0.3;
             let b = *rng.random::<Dir>() * 0.3;
-            world.push(move |worlds: &Worlds| {
+            world.push_node(Parent::Root, move |worlds: &Worlds| {
                 Transform::from_transv(
                     pos + a * worlds.settings.base_time.sin() + b * worlds.settings.base_time.cos(),
                 )
@@ -70,15 +71,15 @@ pub fn build() -> WorldsBuilder {
 
     for (i, _) in points.iter().enumerate() {
         let col: Ref<Color> = world.push(Color::from_oklchf(0.5, 0.3, rng.random_range(-PI..PI)));
-        world.push_visual((transforms[i], col, Sphere(local_sphere)));
+        world.push_visual((nodes[i], col, Sphere(local_sphere)));
     }
     for i in 0..points.len() {
         for i2 in graph.iter_neighboors(i) {
             if i2 <= i {
                 continue;
             }
-            let p1 = transforms[i];
-            let p2 = transforms[i2];
+            let p1 = nodes[i].global();
+            let p2 = nodes[i2].global();
             let tr = world.push(move |worlds: &Worlds| {
                 let p1pos = p1.update(worlds).trans();
                 let p2pos = p2.update(worlds).trans();
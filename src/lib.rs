@@ -6,14 +6,34 @@ pub mod app;
 pub mod datastrutures;
 pub mod logger;
 pub mod math;
+pub mod profiler;
 pub mod render_registry;
 pub mod settings;
 pub mod utils;
 pub mod world;
 pub mod models;
 
-pub fn run(build_fun: impl FnMut() -> world::world_builder::WorldsBuilder + 'static) {
+fn init_perf_level() {
+    if let Some(level) = settings::PerfLevel::from_env() {
+        settings::set_perf_level(level);
+    }
+}
+
+pub fn run(
+    build_fun: impl FnMut() -> world::world_builder::WorldsBuilder + 'static,
+) -> Result<(), app::StartupError> {
+    logger::init_logger();
+    init_perf_level();
+    let mut app = App::new(build_fun)?;
+    app.run()
+}
+
+/// Renders a fixed time range without opening a window
+pub fn export(
+    build_fun: impl FnMut() -> world::world_builder::WorldsBuilder,
+    settings: &app::ExportSettings,
+) -> std::io::Result<usize> {
     logger::init_logger();
-    let mut app = App::new(build_fun);
-    app.run();
+    init_perf_level();
+    app::export_frames(build_fun, settings)
 }
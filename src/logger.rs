@@ -2,16 +2,21 @@ use tracing_subscriber::util::SubscriberInitExt;
 use tracing_subscriber::{filter::EnvFilter, fmt, layer::SubscriberExt};
 
 pub fn init_logger() {
-    tracing_subscriber::registry()
-        .with(fmt::layer().without_time())
-        .with(EnvFilter::new(
-            r#"
+    init_logger_with(
+        &r#"
             info,
             wgpu_hal=warn,
             wgpu_core=warn,
             naga=warn,
         "#
-            .replace([' ', '\n', '\t'], ""),
-        ))
+        .replace([' ', '\n', '\t'], ""),
+    )
+}
+
+/// Uses a custom filter, with the `tracing_subscriber::EnvFilter` syntax
+pub fn init_logger_with(filter: &str) {
+    tracing_subscriber::registry()
+        .with(fmt::layer().without_time())
+        .with(EnvFilter::new(filter))
         .init()
 }
@@ -0,0 +1,46 @@
+use crate::math::{Mat4, Transform, Vec4, vec3};
+
+/// The volume seen by a camera, as the planes its points are in front of.
+/// The far plane is ignored, the projection being infinite
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Frustum {
+    planes: [Vec4; 5],
+}
+impl Frustum {
+    /// From a projection * view matrix with a depth going from 0 to 1, like `Camera::matrix`
+    pub fn from_matrix(mat: Mat4) -> Self {
+        let arr = mat.to_array();
+        let row = |r: usize| Vec4::new(arr[r], arr[4 + r], arr[8 + r], arr[12 + r]);
+        let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+        Self {
+            planes: [w + x, w - x, w + y, w - y, z],
+        }
+    }
+    /// Whether any part of the box, the image of the [-1, 1] cube, may be seen
+    pub fn intersects_box(&self, tr: Transform) -> bool {
+        let corners: [Vec4; 8] = std::array::from_fn(|i| {
+            let sign = |bit: usize| if i & bit == 0 { -1. } else { 1. };
+            tr.tr_point(vec3(sign(1), sign(2), sign(4))).to_vec4(1.)
+        });
+        self.planes
+            .iter()
+            .all(|plane| corners.iter().any(|corner| plane.dot(*corner) >= 0.))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::Angle;
+
+    #[test]
+    fn culling() {
+        let proj = Mat4::new_perspective_infinite_lh(Angle::from_deg(90.), 1., 0.1);
+        let frustum = Frustum::from_matrix(proj);
+        assert!(frustum.intersects_box(Transform::from_transf(0., 0., 10.)));
+        assert!(!frustum.intersects_box(Transform::from_transf(0., 0., -10.)));
+        assert!(!frustum.intersects_box(Transform::from_transf(20., 0., 10.)));
+        // Partly in front of the camera
+        assert!(frustum.intersects_box(Transform::from_transf(0., 0., -0.5)));
+    }
+}
@@ -1,5 +1,6 @@
 pub mod angle;
 pub mod dir;
+pub mod frustum;
 pub mod mat4;
 pub mod plane;
 pub mod polynomial;
@@ -10,6 +11,7 @@ pub mod vec4;
 
 pub use angle::*;
 pub use dir::*;
+pub use frustum::*;
 pub use mat4::*;
 pub use plane::*;
 pub use polynomial::*;
@@ -0,0 +1,149 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Write as _;
+use std::io::Write;
+use std::path::Path;
+use std::time::Instant;
+
+/// Number of frames kept for each timing
+const WINDOW: usize = 300;
+
+/// Rolling per-frame timings, in milliseconds, recorded only while enabled
+pub struct Profiler {
+    enabled: bool,
+    frame: BTreeMap<String, f32>,
+    timings: BTreeMap<String, VecDeque<f32>>,
+}
+
+pub struct TimingSummary<'a> {
+    pub name: &'a str,
+    pub count: usize,
+    pub min: f32,
+    pub avg: f32,
+    pub p99: f32,
+    pub max: f32,
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Profiler {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            frame: BTreeMap::new(),
+            timings: BTreeMap::new(),
+        }
+    }
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if enabled != self.enabled {
+            self.frame.clear();
+            self.timings.clear();
+        }
+        self.enabled = enabled;
+    }
+    /// Adds a duration to the current frame, the name is only built while enabled
+    pub fn record(&mut self, name: impl FnOnce() -> String, ms: f32) {
+        if self.enabled {
+            *self.frame.entry(name()).or_default() += ms;
+        }
+    }
+    /// Runs `f` and records the time it took
+    pub fn time<T>(&mut self, name: impl FnOnce() -> String, f: impl FnOnce() -> T) -> T {
+        if !self.enabled {
+            return f();
+        }
+        let start = Instant::now();
+        let value = f();
+        self.record(name, start.elapsed().as_secs_f32() * 1000.);
+        value
+    }
+    /// Pushes the durations of the current frame in the rolling windows
+    pub fn end_frame(&mut self) {
+        for (name, ms) in std::mem::take(&mut self.frame) {
+            let samples = self.timings.entry(name).or_default();
+            if samples.len() == WINDOW {
+                samples.pop_front();
+            }
+            samples.push_back(ms);
+        }
+    }
+    pub fn summaries(&self) -> Vec<TimingSummary<'_>> {
+        self.timings
+            .iter()
+            .filter(|(_, samples)| !samples.is_empty())
+            .map(|(name, samples)| {
+                let mut sorted = samples.iter().copied().collect::<Vec<_>>();
+                sorted.sort_by(f32::total_cmp);
+                let p99 = ((sorted.len() - 1) as f32 * 0.99).round() as usize;
+                TimingSummary {
+                    name,
+                    count: sorted.len(),
+                    min: sorted[0],
+                    avg: sorted.iter().sum::<f32>() / sorted.len() as f32,
+                    p99: sorted[p99],
+                    max: sorted[sorted.len() - 1],
+                }
+            })
+            .collect()
+    }
+    /// A table of the summaries, slowest average first
+    pub fn report(&self) -> String {
+        let mut summaries = self.summaries();
+        summaries.sort_by(|a, b| b.avg.total_cmp(&a.avg));
+        let width = summaries.iter().map(|s| s.name.len()).max().unwrap_or(0);
+        let mut out = format!(
+            "{:width$}  {:>6}  {:>8}  {:>8}  {:>8}  {:>8}\n",
+            "timing", "frames", "min ms", "avg ms", "p99 ms", "max ms"
+        );
+        for s in summaries {
+            let _ = writeln!(
+                out,
+                "{:width$}  {:>6}  {:>8.3}  {:>8.3}  {:>8.3}  {:>8.3}",
+                s.name, s.count, s.min, s.avg, s.p99, s.max
+            );
+        }
+        out
+    }
+    pub fn write_csv(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        writeln!(file, "timing,frames,min_ms,avg_ms,p99_ms,max_ms")?;
+        for s in self.summaries() {
+            writeln!(
+                file,
+                "\"{}\",{},{},{},{},{}",
+                s.name, s.count, s.min, s.avg, s.p99, s.max
+            )?;
+        }
+        file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summaries() {
+        let mut profiler = Profiler::new();
+        profiler.record(|| "ignored".to_string(), 1.);
+        profiler.set_enabled(true);
+        for i in 0..=100 {
+            profiler.record(|| "a".to_string(), i as f32);
+            profiler.record(|| "a".to_string(), 1.);
+            profiler.end_frame();
+        }
+        let summaries = profiler.summaries();
+        assert_eq!(summaries.len(), 1);
+        let a = &summaries[0];
+        assert_eq!((a.name, a.count), ("a", 101));
+        assert_eq!((a.min, a.avg, a.p99, a.max), (1., 51., 100., 101.));
+    }
+}
@@ -52,6 +52,10 @@ impl BaseBindings {
             label: Some("Base bind group layout"),
             entries: &layout_entries,
         });
+        Self::with_layout(device, layout)
+    }
+    /// Other bindings sharing the layout, so the same pipelines can use them
+    pub fn with_layout(device: &wgpu::Device, layout: wgpu::BindGroupLayout) -> Self {
         let buffers = EntryType::ARRAY.map(|e| {
             device.create_buffer(&wgpu::BufferDescriptor {
                 size: e.min_size().next_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT),
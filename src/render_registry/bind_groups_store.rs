@@ -1,10 +1,12 @@
 use crate::render_registry::alloc::BufferAllocator;
-use crate::world::primitives::StoreLabel;
+use crate::world::primitives::{PrimitiveStoresHolder, StoreLabel};
 use tracing::{info, info_span};
 
 pub struct StoreBindings {
     pub bind_group: wgpu::BindGroup,
     pub buffers: [wgpu::Buffer; StoreLabel::COUNT],
+    /// Whether the buffers were written once, after which only the changes are uploaded
+    filled: bool,
 }
 impl StoreBindings {
     pub fn new(
@@ -25,6 +27,20 @@ impl StoreBindings {
             info!("{}", occs[label as usize]);
         }
 
+        let layout = Self::layout(device);
+        let bindings = allocs
+            .iter()
+            .map(|alloc| Self::with_layout(device, &layout, alloc))
+            .collect::<Vec<_>>();
+
+        info!(
+            "Succesfully created {} store bindings",
+            StoreLabel::COUNT * allocs.len()
+        );
+
+        (bindings, layout)
+    }
+    pub fn layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
         let layout_entries = StoreLabel::ARRAY.map(|label| wgpu::BindGroupLayoutEntry {
             binding: label.bind(),
             count: None,
@@ -36,60 +52,68 @@ impl StoreBindings {
             },
         });
 
-        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("Store bind group layout"),
             entries: &layout_entries,
+        })
+    }
+    /// The bindings of one world
+    pub fn with_layout(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        alloc: &BufferAllocator,
+    ) -> Self {
+        let buffers = StoreLabel::ARRAY.map(|label| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                size: (label.struct_size() * (alloc.get_store_count(label) + 1))
+                    as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+                label: Some(&format!("Buffer of {}", label.name())),
+            })
         });
 
-        let bindings = allocs
-            .iter()
-            .map(|alloc| {
-                let buffers = StoreLabel::ARRAY.map(|label| {
-                    device.create_buffer(&wgpu::BufferDescriptor {
-                        size: (label.struct_size() * (alloc.get_store_count(label) + 1))
-                            as wgpu::BufferAddress,
-                        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
-                        mapped_at_creation: false,
-                        label: Some(&format!("Buffer of {}", label.name())),
-                    })
-                });
-
-                let entries = StoreLabel::ARRAY.map(|label| wgpu::BindGroupEntry {
-                    binding: label.bind(),
-                    resource: buffers[label as usize].as_entire_binding(),
-                });
-
-                let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-                    label: Some("Store bind group"),
-                    entries: &entries,
-                    layout: &layout,
-                });
-
-                Self {
-                    bind_group,
-                    buffers,
-                }
-            })
-            .collect::<Vec<_>>();
+        let entries = StoreLabel::ARRAY.map(|label| wgpu::BindGroupEntry {
+            binding: label.bind(),
+            resource: buffers[label as usize].as_entire_binding(),
+        });
 
-        info!(
-            "Succesfully created {} store bindings",
-            StoreLabel::COUNT * allocs.len()
-        );
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Store bind group"),
+            entries: &entries,
+            layout,
+        });
 
-        (bindings, layout)
+        Self {
+            bind_group,
+            buffers,
+            filled: false,
+        }
     }
     pub fn put(&self, render_pass: &mut wgpu::RenderPass) {
         render_pass.set_bind_group(1, &self.bind_group, &[]);
     }
-    pub fn views<'a>(
-        &'a self,
-        queue: &'a wgpu::Queue,
-    ) -> [Option<wgpu::QueueWriteBufferView<'a>>; StoreLabel::COUNT] {
-        self.buffers.each_ref().map(|buf| {
-            wgpu::BufferSize::try_from(buf.size())
-                .ok()
-                .and_then(|size| queue.write_buffer_with(buf, 0, size))
-        })
+    /// Uploads the entries changed since the last write, or all of them into new buffers
+    pub fn write(&mut self, queue: &wgpu::Queue, stores: &PrimitiveStoresHolder) {
+        for label in StoreLabel::ARRAY {
+            let mut spans = stores.take_dirty(label);
+            if !self.filled {
+                spans.clear();
+                spans.push(0..label.len(stores));
+            }
+            for span in spans {
+                let size = (label.struct_size() * span.len()) as wgpu::BufferAddress;
+                let offset = (label.struct_size() * span.start) as wgpu::BufferAddress;
+                let Some(size) = wgpu::BufferSize::new(size) else {
+                    continue;
+                };
+                if let Some(mut view) =
+                    queue.write_buffer_with(&self.buffers[label as usize], offset, size)
+                {
+                    label.write(bytemuck::cast_slice_mut(&mut view), stores, span);
+                }
+            }
+        }
+        self.filled = true;
     }
 }
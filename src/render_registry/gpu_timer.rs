@@ -0,0 +1,137 @@
+use std::sync::{Arc, OnceLock};
+use tracing::{error, info};
+
+/// Timestamps written by a frame, two per timed section
+const MAX_QUERIES: u32 = 1024;
+
+/// A frame whose timestamps are being mapped
+struct PendingRead {
+    labels: Vec<String>,
+    mapped: Arc<OnceLock<Result<(), wgpu::BufferAsyncError>>>,
+}
+
+/// Times sections of a render pass on the GPU, when the adapter supports timestamps in passes.
+/// The timings are read without waiting for the GPU, so they arrive a frame or more later
+pub struct GpuTimer {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    read_buffer: wgpu::Buffer,
+    /// Nanoseconds by tick
+    period: f32,
+    labels: Vec<String>,
+    pending: Option<PendingRead>,
+}
+impl GpuTimer {
+    pub const FEATURES: wgpu::Features =
+        wgpu::Features::TIMESTAMP_QUERY.union(wgpu::Features::TIMESTAMP_QUERY_INSIDE_PASSES);
+
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Option<Self> {
+        if !device.features().contains(Self::FEATURES) {
+            info!("Timestamp queries aren't supported, no GPU timings");
+            return None;
+        }
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("Timestamp queries"),
+            ty: wgpu::QueryType::Timestamp,
+            count: MAX_QUERIES,
+        });
+        let size = MAX_QUERIES as u64 * wgpu::QUERY_SIZE as u64;
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Timestamp resolve buffer"),
+            size,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let read_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Timestamp readback buffer"),
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        Some(Self {
+            query_set,
+            resolve_buffer,
+            read_buffer,
+            period: queue.get_timestamp_period(),
+            labels: Vec::new(),
+            pending: None,
+        })
+    }
+    /// Starts a section, None if there is no query left for this frame,
+    /// or if the timings of a previous frame are still being read
+    pub fn begin(&mut self, render_pass: &mut wgpu::RenderPass, label: String) -> Option<u32> {
+        let index = self.labels.len() as u32 * 2;
+        if index + 2 > MAX_QUERIES || self.pending.is_some() {
+            return None;
+        }
+        self.labels.push(label);
+        render_pass.write_timestamp(&self.query_set, index);
+        Some(index)
+    }
+    pub fn end(&self, render_pass: &mut wgpu::RenderPass, section: Option<u32>) {
+        if let Some(index) = section {
+            render_pass.write_timestamp(&self.query_set, index + 1);
+        }
+    }
+    pub fn resolve(&self, encoder: &mut wgpu::CommandEncoder) {
+        let count = self.labels.len() as u32 * 2;
+        if count == 0 {
+            return;
+        }
+        encoder.resolve_query_set(&self.query_set, 0..count, &self.resolve_buffer, 0);
+        let size = count as u64 * wgpu::QUERY_SIZE as u64;
+        encoder.copy_buffer_to_buffer(&self.resolve_buffer, 0, &self.read_buffer, 0, size);
+    }
+    /// Starts reading the submitted frame, and returns the duration of each section
+    /// of the last frame read, in milliseconds, once the GPU is done with it
+    pub fn read(&mut self, device: &wgpu::Device) -> Vec<(String, f32)> {
+        if let Err(err) = device.poll(wgpu::PollType::Poll) {
+            error!("Failed to poll the timestamps: {err}");
+        }
+        let timings = match self.pending.as_ref().and_then(|p| p.mapped.get()) {
+            Some(Ok(())) => self.read_mapped(),
+            Some(Err(err)) => {
+                error!("Failed to map the timestamp buffer: {err}");
+                self.pending = None;
+                Vec::new()
+            }
+            None => Vec::new(),
+        };
+        if self.pending.is_none() && !self.labels.is_empty() {
+            let mapped = Arc::new(OnceLock::new());
+            let done = mapped.clone();
+            self.read_buffer
+                .slice(..(self.labels.len() * 2 * wgpu::QUERY_SIZE as usize) as u64)
+                .map_async(wgpu::MapMode::Read, move |res| {
+                    let _ = done.set(res);
+                });
+            self.pending = Some(PendingRead {
+                labels: std::mem::take(&mut self.labels),
+                mapped,
+            });
+        }
+        timings
+    }
+    fn read_mapped(&mut self) -> Vec<(String, f32)> {
+        let Some(pending) = self.pending.take() else {
+            return Vec::new();
+        };
+        let slice = self
+            .read_buffer
+            .slice(..(pending.labels.len() * 2 * wgpu::QUERY_SIZE as usize) as u64);
+        let data = slice.get_mapped_range();
+        let ticks: &[u64] = bytemuck::cast_slice(&data);
+        let timings = pending
+            .labels
+            .into_iter()
+            .zip(ticks.chunks_exact(2))
+            .map(|(label, t)| {
+                let ns = t[1].saturating_sub(t[0]) as f32 * self.period;
+                (label, ns / 1_000_000.)
+            })
+            .collect();
+        drop(data);
+        self.read_buffer.unmap();
+        timings
+    }
+}
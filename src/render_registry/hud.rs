@@ -0,0 +1,181 @@
+use crate::render_registry::font::packed_glyph;
+use bytemuck::{Pod, Zeroable};
+use std::borrow::Cow;
+use tracing::{info, info_span};
+
+/// Size of a glyph cell in pixels, before scaling
+const CELL_SIZE: [f32; 2] = [6., 8.];
+const MARGIN: f32 = 8.;
+
+#[derive(Pod, Zeroable, Clone, Copy)]
+#[repr(C)]
+struct GlyphInstance {
+    pos: [f32; 2],
+    bits: [u32; 2],
+}
+
+#[derive(Pod, Zeroable, Clone, Copy)]
+#[repr(C)]
+struct HudSettings {
+    screen_size: [f32; 2],
+    scale: f32,
+    _padding: f32,
+}
+
+/// Draws lines of text over the scene, with its own pipeline and a built-in bitmap font
+pub struct HudRenderer {
+    pipeline: wgpu::RenderPipeline,
+    bind_group: wgpu::BindGroup,
+    settings_buffer: wgpu::Buffer,
+    instance_buffer: wgpu::Buffer,
+    capacity: u64,
+    nb_glyph: u32,
+    scale: f32,
+}
+impl HudRenderer {
+    pub fn new(device: &wgpu::Device, texture_format: wgpu::TextureFormat) -> Self {
+        let _span = info_span!("hud").entered();
+        info!("Creating hud renderer");
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Hud shader"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!("../shaders/hud.wgsl"))),
+        });
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Hud bind group layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                count: None,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+            }],
+        });
+        let settings_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Hud settings buffer"),
+            size: size_of::<HudSettings>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Hud bind group"),
+            layout: &layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: settings_buffer.as_entire_binding(),
+            }],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Hud pipeline layout"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Hud pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_hud"),
+                buffers: &[wgpu::VertexBufferLayout {
+                    step_mode: wgpu::VertexStepMode::Instance,
+                    array_stride: size_of::<GlyphInstance>() as wgpu::BufferAddress,
+                    attributes: &wgpu::vertex_attr_array![0 => Float32x2, 1 => Uint32x2],
+                }],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_hud"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: texture_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+        let capacity = 256;
+        Self {
+            pipeline,
+            bind_group,
+            settings_buffer,
+            instance_buffer: Self::create_instance_buffer(device, capacity),
+            capacity,
+            nb_glyph: 0,
+            scale: 2.,
+        }
+    }
+    fn create_instance_buffer(device: &wgpu::Device, capacity: u64) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Hud instance buffer"),
+            size: capacity * size_of::<GlyphInstance>() as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+    /// Lays out the lines from the top left corner of the screen
+    pub fn set_text(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        screen_size: (u32, u32),
+        lines: &[String],
+    ) {
+        let [cell_w, cell_h] = CELL_SIZE.map(|s| s * self.scale);
+        let glyphs = lines
+            .iter()
+            .enumerate()
+            .flat_map(|(y, line)| {
+                line.chars().enumerate().map(move |(x, c)| GlyphInstance {
+                    pos: [MARGIN + x as f32 * cell_w, MARGIN + y as f32 * cell_h],
+                    bits: packed_glyph(c),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        if glyphs.len() as u64 > self.capacity {
+            self.capacity = (glyphs.len() as u64).next_power_of_two();
+            self.instance_buffer = Self::create_instance_buffer(device, self.capacity);
+        }
+        queue.write_buffer(&self.instance_buffer, 0, bytemuck::cast_slice(&glyphs));
+        queue.write_buffer(
+            &self.settings_buffer,
+            0,
+            bytemuck::bytes_of(&HudSettings {
+                screen_size: [screen_size.0 as f32, screen_size.1 as f32],
+                scale: self.scale,
+                _padding: 0.,
+            }),
+        );
+        self.nb_glyph = glyphs.len() as u32;
+    }
+    /// Draws over the content of the view
+    pub fn render(&self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+        if self.nb_glyph == 0 {
+            return;
+        }
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Hud render pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            ..Default::default()
+        });
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.instance_buffer.slice(..));
+        render_pass.draw(0..6, 0..self.nb_glyph);
+    }
+}
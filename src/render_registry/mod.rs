@@ -2,8 +2,12 @@ pub mod alloc;
 pub mod bind_group_base;
 pub mod bind_groups_store;
 pub mod depth;
+pub mod font;
+pub mod gpu_timer;
+pub mod hud;
 pub mod materials;
 pub mod mesh_builder;
+pub mod offscreen;
 pub mod pipelines;
 pub mod prefabs;
 pub mod registry;
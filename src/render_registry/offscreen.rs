@@ -0,0 +1,101 @@
+use tracing::{error, info, info_span};
+
+/// A render target that lives only on the gpu, used when there is no window to draw into
+pub struct OffscreenTarget {
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    pub config: wgpu::SurfaceConfiguration,
+}
+impl OffscreenTarget {
+    pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+    pub fn new(device: &wgpu::Device, width: u32, height: u32) -> Self {
+        let _span = info_span!("offscreen_target").entered();
+        info!("Creating offscreen target {width}x{height}");
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Offscreen target"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::FORMAT,
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        // Only used to build the registry, the same way as a window surface
+        let config = wgpu::SurfaceConfiguration {
+            format: Self::FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            width,
+            height,
+            alpha_mode: wgpu::CompositeAlphaMode::Auto,
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+            present_mode: wgpu::PresentMode::Fifo,
+        };
+        Self {
+            texture,
+            view,
+            config,
+        }
+    }
+    /// Tightly packed RGBA8 pixels, row by row from the top left
+    pub fn read_pixels(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> Vec<u8> {
+        read_texture(device, queue, &self.texture)
+    }
+}
+
+/// Copies a 4 bytes per texel texture back to the cpu, removing the row padding
+pub fn read_texture(device: &wgpu::Device, queue: &wgpu::Queue, texture: &wgpu::Texture) -> Vec<u8> {
+    let _span = info_span!("read_texture").entered();
+    let size = texture.size();
+    let unpadded_row = size.width * 4;
+    let padded_row = unpadded_row.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Readback buffer"),
+        size: (padded_row * size.height) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Readback encoder"),
+    });
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::TexelCopyBufferInfo {
+            buffer: &buffer,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_row),
+                rows_per_image: Some(size.height),
+            },
+        },
+        size,
+    );
+    queue.submit([encoder.finish()]);
+
+    let slice = buffer.slice(..);
+    slice.map_async(wgpu::MapMode::Read, |res| {
+        if let Err(err) = res {
+            error!("Failed to map the readback buffer: {err}");
+        }
+    });
+    device
+        .poll(wgpu::PollType::Wait)
+        .expect("Failed to wait for the readback");
+
+    let data = slice.get_mapped_range();
+    let mut pixels = Vec::with_capacity((unpadded_row * size.height) as usize);
+    for row in data.chunks(padded_row as usize) {
+        pixels.extend_from_slice(&row[..unpadded_row as usize]);
+    }
+    drop(data);
+    buffer.unmap();
+    pixels
+}
@@ -3,6 +3,7 @@ use crate::render_registry::materials::MaterialType;
 use crate::render_registry::prefabs::VertexPoss;
 use crate::render_registry::shaders::Shaders;
 use crate::render_registry::vertex::{AuxiliaryBufferDesc, VertexType};
+use crate::settings::{PerfLevel, current_perf_level};
 use std::num::NonZeroU64;
 use tracing::{info, info_span};
 use wgpu::util::DeviceExt;
@@ -11,6 +12,29 @@ enum AuxiliaryBuffer {
     VertexPoss(wgpu::Buffer),
 }
 
+fn create_aux_buffers(
+    device: &wgpu::Device,
+    name: &str,
+    vertex: VertexType,
+    level: PerfLevel,
+) -> Vec<AuxiliaryBuffer> {
+    vertex
+        .aux_buffers_at(level)
+        .into_iter()
+        .map(|desc| match desc {
+            AuxiliaryBufferDesc::VertexPoss(VertexPoss { content, .. }) => {
+                AuxiliaryBuffer::VertexPoss(device.create_buffer_init(
+                    &wgpu::util::BufferInitDescriptor {
+                        label: Some(&format!("Auxiliary buffer {name}")),
+                        usage: wgpu::BufferUsages::VERTEX,
+                        contents: bytemuck::cast_slice(content),
+                    },
+                ))
+            }
+        })
+        .collect()
+}
+
 fn create_pipeline(
     device: &wgpu::Device,
     pipeline_layout: &wgpu::PipelineLayout,
@@ -65,6 +89,11 @@ pub struct Pipeline {
     instance_buffer: wgpu::Buffer,
     aux_buffers: Vec<AuxiliaryBuffer>,
     nb_instance: NonZeroU64,
+    nb_vertex: u32,
+    /// The perf level of `aux_buffers`
+    level: PerfLevel,
+    /// The prefabs of the other levels, created when a world asks for them
+    detail_buffers: Vec<(PerfLevel, Vec<AuxiliaryBuffer>, u32)>,
     vertex: VertexType,
     material: MaterialType,
     shaders: Shaders,
@@ -102,25 +131,15 @@ impl Pipeline {
             attributes: instance_label.attrs(),
         }];
 
-        let mut aux_buffers = Vec::new();
-        for aux_buf_label in vertex.aux_buffers() {
-            match aux_buf_label {
-                AuxiliaryBufferDesc::VertexPoss(VertexPoss { label, content, .. }) => {
-                    buffers_descriptor.push(wgpu::VertexBufferLayout {
-                        step_mode: wgpu::VertexStepMode::Vertex,
-                        array_stride: label.elt_size() as wgpu::BufferAddress,
-                        attributes: label.attrs(),
-                    });
-                    aux_buffers.push(AuxiliaryBuffer::VertexPoss(device.create_buffer_init(
-                        &wgpu::util::BufferInitDescriptor {
-                            label: Some(&format!("Auxiliary buffer {name}")),
-                            usage: wgpu::BufferUsages::VERTEX,
-                            contents: bytemuck::cast_slice(&content),
-                        },
-                    )));
-                }
-            }
+        let level = current_perf_level();
+        for AuxiliaryBufferDesc::VertexPoss(VertexPoss { label, .. }) in vertex.aux_buffers_at(level) {
+            buffers_descriptor.push(wgpu::VertexBufferLayout {
+                step_mode: wgpu::VertexStepMode::Vertex,
+                array_stride: label.elt_size() as wgpu::BufferAddress,
+                attributes: label.attrs(),
+            });
         }
+        let aux_buffers = create_aux_buffers(device, &name, vertex, level);
         let render_pipeline = create_pipeline(
             device,
             &pipeline_layout,
@@ -145,6 +164,10 @@ impl Pipeline {
             aux_buffers,
             instance_buffer,
             nb_instance,
+            // The prefabs may change with the perf level, the aux buffers don't
+            nb_vertex: vertex.nb_vertex_at(level),
+            level,
+            detail_buffers: Vec::new(),
             vertex,
             wireframe_render_pipeline: None,
             material,
@@ -168,7 +191,53 @@ impl Pipeline {
             wgpu::PolygonMode::Line,
         ))
     }
-    pub fn render(&mut self, render_pass: &mut wgpu::RenderPass, render_wires: bool) {
+    /// Builds the render pipeline with other shaders, without replacing the current one
+    pub fn create_render_pipeline(&self, shaders: &Shaders) -> wgpu::RenderPipeline {
+        create_pipeline(
+            &self.device,
+            &self.pipeline_layout,
+            &self.name,
+            &self.buffers_descriptor,
+            shaders.clone(),
+            self.vertex,
+            self.material,
+            &self.texture_format,
+            wgpu::PolygonMode::Fill,
+        )
+    }
+    /// The wireframe pipeline is regenerated when needed
+    pub fn set_render_pipeline(&mut self, shaders: Shaders, render_pipeline: wgpu::RenderPipeline) {
+        self.shaders = shaders;
+        self.render_pipeline = render_pipeline;
+        self.wireframe_render_pipeline = None;
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    fn prefab(&mut self, detail: Option<PerfLevel>) -> (&[AuxiliaryBuffer], u32) {
+        let Some(level) = detail.filter(|&level| level != self.level) else {
+            return (&self.aux_buffers, self.nb_vertex);
+        };
+        let idx = match self.detail_buffers.iter().position(|(l, ..)| *l == level) {
+            Some(idx) => idx,
+            None => {
+                info!("Creating the {} prefab of {}", level.name(), self.name);
+                let buffers = create_aux_buffers(&self.device, &self.name, self.vertex, level);
+                let nb_vertex = self.vertex.nb_vertex_at(level);
+                self.detail_buffers.push((level, buffers, nb_vertex));
+                self.detail_buffers.len() - 1
+            }
+        };
+        let (_, buffers, nb_vertex) = &self.detail_buffers[idx];
+        (buffers, *nb_vertex)
+    }
+    /// `detail` replaces the prefab level of the pipeline
+    pub fn render(
+        &mut self,
+        render_pass: &mut wgpu::RenderPass,
+        render_wires: bool,
+        detail: Option<PerfLevel>,
+    ) {
         render_pass.set_pipeline(&self.render_pipeline);
 
         if render_wires {
@@ -185,14 +254,16 @@ impl Pipeline {
             }
         }
         render_pass.set_vertex_buffer(0, self.instance_buffer.slice(..));
-        for (i, aux_buffer) in self.aux_buffers.iter().enumerate() {
+        let nb_instance = self.nb_instance.get() as u32;
+        let (aux_buffers, nb_vertex) = self.prefab(detail);
+        for (i, aux_buffer) in aux_buffers.iter().enumerate() {
             match &aux_buffer {
                 AuxiliaryBuffer::VertexPoss(buffer) => {
                     render_pass.set_vertex_buffer((i + 1) as u32, buffer.slice(..));
                 }
             }
         }
-        render_pass.draw(0..self.vertex.nb_vertex(), 0..self.nb_instance.get() as u32);
+        render_pass.draw(0..nb_vertex, 0..nb_instance);
     }
 
     pub fn view_instance<'a>(&'a self, queue: &'a wgpu::Queue) -> wgpu::QueueWriteBufferView<'a> {
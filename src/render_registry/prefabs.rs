@@ -2,11 +2,12 @@ use crate::math::Vec3;
 use crate::render_registry::vertex::{Pos2Vertex, Pos3Vertex, VertexBufferLabel};
 use crate::utils::{Length, VectorSpace};
 use std::f32::consts::TAU;
-use std::sync::LazyLock;
+use std::ops::Deref;
+use std::sync::{LazyLock, OnceLock};
 use tracing::info;
 
 use super::vertex::{TilePosVertex, VertexType};
-use crate::settings::perf_level;
+use crate::settings::{PerfLevel, current_perf_level, perf_level, with_perf_level};
 
 #[derive(Clone, Copy, Debug)]
 pub struct VertexPoss {
@@ -15,6 +16,32 @@ pub struct VertexPoss {
     pub len: u32,
 }
 
+/// A prefab generated lazily once for each perf level, for the current one when dereferenced
+pub struct PerLevel<T> {
+    values: [OnceLock<T>; PerfLevel::COUNT],
+    make: fn() -> T,
+}
+impl<T> PerLevel<T> {
+    const fn new(make: fn() -> T) -> Self {
+        Self {
+            values: [const { OnceLock::new() }; PerfLevel::COUNT],
+            make,
+        }
+    }
+}
+impl<T> PerLevel<T> {
+    /// Generated as if it was the current level
+    pub fn at(&self, level: PerfLevel) -> &T {
+        self.values[level as usize].get_or_init(|| with_perf_level(level, self.make))
+    }
+}
+impl<T> Deref for PerLevel<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.at(current_perf_level())
+    }
+}
+
 // Looks good, like a rose
 // pub static CIRCLE_POS: LazyLock<(u32, &'static [u32])> = LazyLock::new(|| {
 //     let mut vertexes = vec![
@@ -56,7 +83,7 @@ pub struct VertexPoss {
 //         .collect::<Vec<_>>();
 //     (vs.len() as u32, bytemuck::cast_slice(vs.leak()))
 // });
-pub static CIRCLE_POS: LazyLock<VertexPoss> = LazyLock::new(|| {
+pub static CIRCLE_POS: PerLevel<VertexPoss> = PerLevel::new(|| {
     let iterations = perf_level!(
         1
         => HighPerf
@@ -106,7 +133,7 @@ pub static CIRCLE_POS: LazyLock<VertexPoss> = LazyLock::new(|| {
     }
 });
 
-pub static FLAT_POS: LazyLock<VertexPoss> = LazyLock::new(|| {
+pub static FLAT_POS: PerLevel<VertexPoss> = PerLevel::new(|| {
     let subdivisions = perf_level!(
         6
         => HighPerf
@@ -143,7 +170,7 @@ pub static FLAT_POS: LazyLock<VertexPoss> = LazyLock::new(|| {
     }
 });
 
-pub static PIPE_POS: LazyLock<VertexPoss> = LazyLock::new(|| {
+pub static PIPE_POS: PerLevel<VertexPoss> = PerLevel::new(|| {
     let subdivisions = perf_level!(
         6
         => HighPerf
@@ -196,7 +223,7 @@ fn make_tiled_tertiary(each_len: u32) -> VertexPoss {
         content: bytemuck::cast_slice(vs.leak()),
     }
 }
-fn make_tiled_pos_base(base: &LazyLock<VertexPoss>) -> (VertexPoss, VertexPoss) {
+fn make_tiled_pos_base(base: &VertexPoss) -> (VertexPoss, VertexPoss) {
     let tertiary = make_tiled_tertiary(base.len);
 
     let secondary = VertexPoss {
@@ -217,7 +244,7 @@ pub static TILED_TRI_POS: LazyLock<VertexPoss> =
     LazyLock::new(|| make_tiled_pos_len(VertexType::Tri.nb_vertex()));
 pub static TILED_CUBE_POS: LazyLock<VertexPoss> =
     LazyLock::new(|| make_tiled_pos_len(VertexType::Cube.nb_vertex()));
-pub static TILED_SPHERE_POS: LazyLock<(VertexPoss, VertexPoss)> =
-    LazyLock::new(|| make_tiled_pos_base(&CIRCLE_POS));
-pub static TILED_FLAT_POS: LazyLock<(VertexPoss, VertexPoss)> =
-    LazyLock::new(|| make_tiled_pos_base(&FLAT_POS));
+pub static TILED_SPHERE_POS: PerLevel<(VertexPoss, VertexPoss)> =
+    PerLevel::new(|| make_tiled_pos_base(&CIRCLE_POS));
+pub static TILED_FLAT_POS: PerLevel<(VertexPoss, VertexPoss)> =
+    PerLevel::new(|| make_tiled_pos_base(&FLAT_POS));
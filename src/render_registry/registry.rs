@@ -1,9 +1,11 @@
 use crate::render_registry::bind_group_base::BaseBindings;
 use crate::render_registry::bind_groups_store::StoreBindings;
+use crate::render_registry::gpu_timer::GpuTimer;
 use crate::render_registry::materials::MaterialType;
 use crate::render_registry::pipelines::Pipeline;
 use crate::render_registry::shaders::Shaders;
 use crate::render_registry::vertex::VertexType;
+use crate::settings::PerfLevel;
 use std::num::NonZeroU64;
 use tracing::{info, info_span};
 
@@ -15,13 +17,73 @@ pub const PIPELINE_COUNT: usize = VertexType::COUNT * MaterialType::COUNT;
 pub struct WorldPipelines {
     pipes: [[Option<Pipeline>; MaterialType::COUNT]; VertexType::COUNT],
     pub activated: bool,
+    /// Prefab level chosen by the world's `LodPolicy`, the global one if `None`
+    pub detail: Option<PerfLevel>,
+}
+impl WorldPipelines {
+    fn new(
+        device: &wgpu::Device,
+        surf_config: &wgpu::SurfaceConfiguration,
+        base_layout: &wgpu::BindGroupLayout,
+        store_layout: &wgpu::BindGroupLayout,
+        shaders: &Shaders,
+        alloc: &BufferAllocator,
+    ) -> Self {
+        Self {
+            pipes: VertexType::ARRAY.map(|vertex| {
+                MaterialType::ARRAY.map(|material| {
+                    let nb_instance = alloc.get_instance_count(vertex, material);
+                    NonZeroU64::new(nb_instance as u64).map(|size| {
+                        Pipeline::new(
+                            vertex,
+                            material,
+                            device,
+                            surf_config,
+                            base_layout,
+                            store_layout,
+                            shaders.clone(),
+                            size,
+                        )
+                    })
+                })
+            }),
+            activated: true,
+            detail: None,
+        }
+    }
+}
+
+/// A part of the render target, in pixels
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ViewportRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+impl ViewportRect {
+    pub fn full(surf_config: &wgpu::SurfaceConfiguration) -> Self {
+        Self {
+            x: 0.,
+            y: 0.,
+            width: surf_config.width as f32,
+            height: surf_config.height as f32,
+        }
+    }
+    pub fn aspect_ratio(&self) -> f32 {
+        self.width / self.height
+    }
 }
 
 pub struct PipelinesRegistry {
+    /// The bindings of the first viewport
     pub base_bindings: BaseBindings,
+    /// The bindings of the other viewports, each with its own camera
+    viewport_bindings: Vec<BaseBindings>,
     pub store_bindings: Vec<StoreBindings>,
+    store_layout: wgpu::BindGroupLayout,
     depth_buffer: DepthBuffer,
-    // shaders: Shaders,
+    shaders: Shaders,
     pub pipes: Vec<WorldPipelines>,
 }
 impl PipelinesRegistry {
@@ -29,55 +91,163 @@ impl PipelinesRegistry {
         device: &wgpu::Device,
         surf_config: &wgpu::SurfaceConfiguration,
         allocs: &[BufferAllocator],
+    ) -> Self {
+        Self::with_shaders(device, surf_config, allocs, Shaders::new(device))
+    }
+    /// Creates a new registry for other buffer allocations, keeping the current shaders
+    pub fn rebuild(
+        &self,
+        device: &wgpu::Device,
+        surf_config: &wgpu::SurfaceConfiguration,
+        allocs: &[BufferAllocator],
+    ) -> Self {
+        Self::with_shaders(device, surf_config, allocs, self.shaders.clone())
+    }
+    fn with_shaders(
+        device: &wgpu::Device,
+        surf_config: &wgpu::SurfaceConfiguration,
+        allocs: &[BufferAllocator],
+        shaders: Shaders,
     ) -> Self {
         let _span = info_span!("registry").entered();
         let base_bindings = BaseBindings::new(device);
         let (store_bindings, store_layout) = StoreBindings::new(device, allocs);
-        let shaders = Shaders::new(device);
         let depth_buffer = DepthBuffer::new(device, surf_config);
 
         let pipes = allocs
             .iter()
-            .map(|alloc| WorldPipelines {
-                pipes: VertexType::ARRAY.map(|vertex| {
-                    MaterialType::ARRAY.map(|material| {
-                        let nb_instance = alloc.get_instance_count(vertex, material);
-                        NonZeroU64::new(nb_instance as u64).map(|size| {
-                            Pipeline::new(
-                                vertex,
-                                material,
-                                device,
-                                surf_config,
-                                &base_bindings.layout,
-                                &store_layout,
-                                shaders.clone(),
-                                size,
-                            )
-                        })
-                    })
-                }),
-                activated: true,
+            .map(|alloc| {
+                WorldPipelines::new(
+                    device,
+                    surf_config,
+                    &base_bindings.layout,
+                    &store_layout,
+                    &shaders,
+                    alloc,
+                )
             })
             .collect();
 
         info!("Succesfully created {} pipelines", PIPELINE_COUNT);
         Self {
             base_bindings,
+            viewport_bindings: Vec::new(),
             store_bindings,
-            // shaders,
+            store_layout,
+            shaders,
             pipes,
             depth_buffer,
         }
     }
+    /// Allocates the buffers of a world added to the scene, its id has to be the next one
+    pub fn add_world(
+        &mut self,
+        device: &wgpu::Device,
+        surf_config: &wgpu::SurfaceConfiguration,
+        alloc: &BufferAllocator,
+    ) {
+        let _span = info_span!("registry_add_world").entered();
+        info!("Creating the buffers of the world {}", self.pipes.len());
+        self.store_bindings.push(StoreBindings::with_layout(
+            device,
+            &self.store_layout,
+            alloc,
+        ));
+        self.pipes.push(WorldPipelines::new(
+            device,
+            surf_config,
+            &self.base_bindings.layout,
+            &self.store_layout,
+            &self.shaders,
+            alloc,
+        ));
+    }
+    /// Frees the buffers of a removed world, keeping minimal empty ones in its place
+    pub fn remove_world(&mut self, device: &wgpu::Device, world_id: usize) {
+        info!("Freeing the buffers of the world {world_id}");
+        let empty = BufferAllocator::new();
+        self.store_bindings[world_id] =
+            StoreBindings::with_layout(device, &self.store_layout, &empty);
+        self.pipes[world_id] = WorldPipelines {
+            pipes: Default::default(),
+            activated: false,
+            detail: None,
+        };
+    }
+    /// Rebuilds every pipeline with the new shaders.
+    /// If any of them fails to validate, the old pipelines are kept
+    pub fn reload_shaders(&mut self, device: &wgpu::Device, shaders: Shaders) -> Result<(), wgpu::Error> {
+        let _span = info_span!("reload_shaders").entered();
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let new_pipes = self
+            .pipes
+            .iter()
+            .map(|wpipes| {
+                wpipes.pipes.each_ref().map(|row| {
+                    row.each_ref().map(|maybe_pipe| {
+                        maybe_pipe
+                            .as_ref()
+                            .map(|pipe| pipe.create_render_pipeline(&shaders))
+                    })
+                })
+            })
+            .collect::<Vec<_>>();
+        if let Some(err) = pollster::block_on(device.pop_error_scope()) {
+            return Err(err);
+        }
+
+        for (wpipes, new_wpipes) in self.pipes.iter_mut().zip(new_pipes) {
+            for (row, new_row) in wpipes.pipes.iter_mut().zip(new_wpipes) {
+                for (maybe_pipe, new_pipe) in row.iter_mut().zip(new_row) {
+                    if let (Some(pipe), Some(new_pipe)) = (maybe_pipe, new_pipe) {
+                        pipe.set_render_pipeline(shaders.clone(), new_pipe);
+                    }
+                }
+            }
+        }
+        self.shaders = shaders;
+        info!("Succesfully reloaded the shaders");
+        Ok(())
+    }
+    /// Makes sure there are bindings for this number of viewports
+    pub fn set_viewport_count(&mut self, device: &wgpu::Device, count: usize) {
+        let extra = count.saturating_sub(1);
+        while self.viewport_bindings.len() < extra {
+            self.viewport_bindings.push(BaseBindings::with_layout(
+                device,
+                self.base_bindings.layout.clone(),
+            ));
+        }
+        self.viewport_bindings.truncate(extra);
+    }
+    pub fn viewport_count(&self) -> usize {
+        self.viewport_bindings.len() + 1
+    }
+    pub fn bindings(&self, viewport: usize) -> &BaseBindings {
+        match viewport {
+            0 => &self.base_bindings,
+            i => &self.viewport_bindings[i - 1],
+        }
+    }
+    pub fn set_time(&self, queue: &wgpu::Queue, time: f32) {
+        for i in 0..self.viewport_count() {
+            self.bindings(i).set_time(queue, time);
+        }
+    }
     pub fn on_resize(&mut self, device: &wgpu::Device, surf_config: &wgpu::SurfaceConfiguration) {
         self.depth_buffer = DepthBuffer::new(device, surf_config);
     }
+    /// Draws every activated world in each viewport, with the bindings of the same index.
+    /// With a timer, each pipeline draw is timed on the GPU.
     pub fn render(
         &mut self,
         encoder: &mut wgpu::CommandEncoder,
         view: &wgpu::TextureView,
         render_wires: bool,
+        viewports: &[ViewportRect],
+        mut timer: Option<&mut GpuTimer>,
     ) {
+        debug_assert!(viewports.len() <= self.viewport_count());
         let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: Some("Render pass"),
             color_attachments: &[Some(wgpu::RenderPassColorAttachment {
@@ -99,18 +269,36 @@ impl PipelinesRegistry {
             ..Default::default()
         });
 
-        self.base_bindings.put(&mut render_pass);
+        for (v, rect) in viewports.iter().enumerate() {
+            render_pass.set_viewport(rect.x, rect.y, rect.width, rect.height, 0., 1.);
+            render_pass.set_scissor_rect(
+                rect.x as u32,
+                rect.y as u32,
+                rect.width as u32,
+                rect.height as u32,
+            );
+            let bindings = match v {
+                0 => &self.base_bindings,
+                i => &self.viewport_bindings[i - 1],
+            };
+            bindings.put(&mut render_pass);
 
-        for i in 0..self.pipes.len() {
-            self.store_bindings[i].put(&mut render_pass);
-            let wpipes = &mut self.pipes[i];
-            if !wpipes.activated {
-                continue;
-            }
-            for r in &mut wpipes.pipes {
-                for maybe_pipe in r {
-                    if let Some(pipe) = maybe_pipe {
-                        pipe.render(&mut render_pass, render_wires);
+            for i in 0..self.pipes.len() {
+                self.store_bindings[i].put(&mut render_pass);
+                let wpipes = &mut self.pipes[i];
+                if !wpipes.activated {
+                    continue;
+                }
+                for r in &mut wpipes.pipes {
+                    for pipe in r.iter_mut().flatten() {
+                        let section = timer.as_deref_mut().and_then(|timer| {
+                            let label = format!("gpu draw world {i} {}", pipe.name());
+                            timer.begin(&mut render_pass, label)
+                        });
+                        pipe.render(&mut render_pass, render_wires, wpipes.detail);
+                        if let Some(timer) = &timer {
+                            timer.end(&mut render_pass, section);
+                        }
                     }
                 }
             }
@@ -1,24 +1,43 @@
 use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tracing::{info, warn};
+use crate::settings::perf_level;
 
 macro_rules! sources {
     (
         $($filename: literal)*
     ) => {
-        concat!($(
-            include_str!(concat!("../shaders/", $filename, ".wgsl")),
-        )*)
+        /// The shader files, in the order they are joined
+        pub const SHADER_FILES: &[&str] = &[$($filename),*];
+
+        fn shader_sources() -> &'static str {
+            concat!($(
+                include_str!(concat!("../shaders/", $filename, ".wgsl")),
+            )*)
+        }
     };
 }
 
-fn shader_sources() -> &'static str {
-    sources!(
-        "bindings"
-        "colors"
-        "shadows"
-        "vertex"
-        "selectors"
-        "fragment"
-    )
+sources!(
+    "bindings"
+    "colors"
+    "shadows"
+    "vertex"
+    "selectors"
+    "fragment"
+);
+
+/// Quality constants depending on the perf level, put before the sources
+fn quality_constants() -> String {
+    let sponge_iterations: u32 = perf_level!(
+        4
+        => HighPerf
+        5
+        => HighDetails
+        6
+    );
+    format!("const SPONGE_ITERATIONS: u32 = {sponge_iterations};\n")
 }
 
 #[derive(Clone, Debug)]
@@ -30,11 +49,77 @@ impl Shaders {
         Self {
             shaders: device.create_shader_module(wgpu::ShaderModuleDescriptor {
                 label: Some("Shaders"),
-                source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(shader_sources())),
+                source: wgpu::ShaderSource::Wgsl(Cow::Owned(quality_constants() + shader_sources())),
             }),
         }
     }
+    /// Compiles shaders at runtime, returning the validation error instead of panicking
+    pub fn from_source(device: &wgpu::Device, source: String) -> Result<Self, wgpu::Error> {
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let shaders = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Shaders (reloaded)"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Owned(source)),
+        });
+        match pollster::block_on(device.pop_error_scope()) {
+            Some(err) => Err(err),
+            None => Ok(Self { shaders }),
+        }
+    }
     pub fn get(&self) -> &wgpu::ShaderModule {
         &self.shaders
     }
 }
+
+fn shader_path(directory: &Path, filename: &str) -> PathBuf {
+    directory.join(format!("{filename}.wgsl"))
+}
+
+/// Polls the modification times of the shader files on disk
+pub struct ShaderWatcher {
+    directory: PathBuf,
+    modified: Vec<Option<SystemTime>>,
+}
+impl Default for ShaderWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl ShaderWatcher {
+    /// The sources are considered modified at the first poll, so they get loaded from disk
+    pub fn new() -> Self {
+        let directory = PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/src/shaders"));
+        info!("Watching the shaders in {directory:?}");
+        Self {
+            directory,
+            modified: vec![None; SHADER_FILES.len()],
+        }
+    }
+    /// Whether any file changed since the last poll
+    pub fn poll(&mut self) -> bool {
+        let mut changed = false;
+        for (filename, last) in SHADER_FILES.iter().zip(&mut self.modified) {
+            let path = shader_path(&self.directory, filename);
+            match std::fs::metadata(&path).and_then(|m| m.modified()) {
+                Ok(time) if Some(time) != *last => {
+                    *last = Some(time);
+                    changed = true;
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    if last.take().is_some() {
+                        warn!("Can't watch the shader {path:?} anymore: {err}");
+                    }
+                }
+            }
+        }
+        changed
+    }
+    /// Joins the current content of the files, like the embedded sources
+    pub fn read_sources(&self) -> std::io::Result<String> {
+        let mut sources = quality_constants();
+        for filename in SHADER_FILES {
+            sources.push_str(&std::fs::read_to_string(shader_path(&self.directory, filename))?);
+        }
+        Ok(sources)
+    }
+}
@@ -1,4 +1,5 @@
 use crate::render_registry::prefabs::{CIRCLE_POS, FLAT_POS, VertexPoss, PIPE_POS, TILED_TRI_POS};
+use crate::settings::{PerfLevel, current_perf_level};
 use crate::utils::array_key;
 use bytemuck::{Pod, Zeroable};
 
@@ -8,8 +9,11 @@ use bytemuck::{Pod, Zeroable};
 // 2 -> global_facts_material
 // 3 -> material
 // 4 -> tilematrix
-// 20 -> pos
-// 21 -> tile_pos TODO
+// 5 -> pos
+// 6 -> tile_pos TODO
+
+/// The `max_vertex_attributes` limit needed by the highest location above
+pub const REQUIRED_VERTEX_ATTRIBUTES: u32 = 7;
 
 pub trait VertexLike: bytemuck::AnyBitPattern + bytemuck::NoUninit {
     const SIZE: wgpu::BufferAddress;
@@ -105,19 +109,19 @@ impl TiledTriVertex {
 
 new_vertex!(
     Pos3Vertex {
-        pos: [f32; 3]: [20 => Float32x3],
+        pos: [f32; 3]: [5 => Float32x3],
     } -> 3;
 );
 
 new_vertex!(
     Pos2Vertex {
-        pos: [f32; 2]: [20 => Float32x2],
+        pos: [f32; 2]: [5 => Float32x2],
     } -> 2;
 );
 
 new_vertex!(
     TilePosVertex {
-        pos: [f32; 2]: [21 => Float32x2],
+        pos: [f32; 2]: [6 => Float32x2],
     } -> 2;
 );
 
@@ -155,23 +159,31 @@ impl VertexType {
             Self::Pipe => VertexBufferLabel::Pipe,
         }
     }
+    /// At the current perf level
     pub fn aux_buffers(&self) -> Vec<AuxiliaryBufferDesc> {
+        self.aux_buffers_at(current_perf_level())
+    }
+    pub fn aux_buffers_at(&self, level: PerfLevel) -> Vec<AuxiliaryBufferDesc> {
         match self {
-            Self::Sphere => vec![AuxiliaryBufferDesc::VertexPoss(*CIRCLE_POS)],
-            Self::Poly4x4 => vec![AuxiliaryBufferDesc::VertexPoss(*FLAT_POS)],
+            Self::Sphere => vec![AuxiliaryBufferDesc::VertexPoss(*CIRCLE_POS.at(level))],
+            Self::Poly4x4 => vec![AuxiliaryBufferDesc::VertexPoss(*FLAT_POS.at(level))],
             Self::Cube | Self::Tri => Vec::new(),
             Self::TiledTri => vec![AuxiliaryBufferDesc::VertexPoss(*TILED_TRI_POS)],
-            Self::Pipe => vec![AuxiliaryBufferDesc::VertexPoss(*PIPE_POS)],
+            Self::Pipe => vec![AuxiliaryBufferDesc::VertexPoss(*PIPE_POS.at(level))],
         }
     }
+    /// At the current perf level
     pub fn nb_vertex(&self) -> u32 {
+        self.nb_vertex_at(current_perf_level())
+    }
+    pub fn nb_vertex_at(&self, level: PerfLevel) -> u32 {
         match self {
-            Self::Sphere => CIRCLE_POS.len,
-            Self::Poly4x4 => FLAT_POS.len,
+            Self::Sphere => CIRCLE_POS.at(level).len,
+            Self::Poly4x4 => FLAT_POS.at(level).len,
             Self::Tri => 3,
             Self::Cube => 36,
             Self::TiledTri => TILED_TRI_POS.len,
-            Self::Pipe => PIPE_POS.len,
+            Self::Pipe => PIPE_POS.at(level).len,
         }
     }
 }
@@ -1,12 +1,79 @@
-#[derive(Ord, PartialOrd, PartialEq, Eq)]
-pub enum PerfLevel {
-    VeryHighPerf,
-    HighPerf,
-    AveragePerf,
-    HighDetails,
-    VeryHighDetails,
+use std::cell::Cell;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+use tracing::{info, warn};
+
+use crate::utils::array_key;
+
+array_key!(
+    pub enum PerfLevel {
+        VeryHighPerf,
+        HighPerf,
+        AveragePerf,
+        HighDetails,
+        VeryHighDetails,
+    }
+);
+impl PerfLevel {
+    pub const DEFAULT: Self = Self::HighPerf;
+    const ENV_VAR: &'static str = "SPACE_ANIMATION_PERF_LEVEL";
+
+    /// The next level towards more details, saturating
+    pub fn more_details(self) -> Self {
+        Self::ARRAY[(self as usize + 1).min(Self::COUNT - 1)]
+    }
+    /// The next level towards more performance, saturating
+    pub fn more_perf(self) -> Self {
+        Self::ARRAY[(self as usize).saturating_sub(1)]
+    }
+    /// Reads the level from the SPACE_ANIMATION_PERF_LEVEL environment variable
+    pub fn from_env() -> Option<Self> {
+        let value = std::env::var(Self::ENV_VAR).ok()?;
+        value
+            .parse()
+            .inspect_err(|err| warn!("Ignoring {}: {err}", Self::ENV_VAR))
+            .ok()
+    }
+}
+impl FromStr for PerfLevel {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ARRAY
+            .into_iter()
+            .find(|level| level.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                let names = Self::ARRAY.map(|level| level.name()).join(", ");
+                format!("unknown perf level `{s}`, expected one of {names}")
+            })
+    }
+}
+
+static PERF_LEVEL: AtomicU8 = AtomicU8::new(PerfLevel::DEFAULT as u8);
+
+thread_local! {
+    static SCOPED_PERF_LEVEL: Cell<Option<PerfLevel>> = const { Cell::new(None) };
+}
+
+pub fn current_perf_level() -> PerfLevel {
+    SCOPED_PERF_LEVEL
+        .get()
+        .unwrap_or_else(|| PerfLevel::ARRAY[PERF_LEVEL.load(Ordering::Relaxed) as usize])
 }
-pub const PERF_LEVEL: PerfLevel = PerfLevel::HighPerf;
+/// Runs `f` as if the level was `level`, on this thread only
+pub fn with_perf_level<T>(level: PerfLevel, f: impl FnOnce() -> T) -> T {
+    let previous = SCOPED_PERF_LEVEL.replace(Some(level));
+    let res = f();
+    SCOPED_PERF_LEVEL.set(previous);
+    res
+}
+/// The prefabs, target fps and shaders only follow the new level once they are recreated
+pub fn set_perf_level(level: PerfLevel) {
+    info!("Setting perf level to {}", level.name());
+    PERF_LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+/// Reads the shaders from disk and reloads them when they change
+pub const HOT_RELOAD_SHADERS: bool = cfg!(debug_assertions);
 
 macro_rules! perf_level {
     (
@@ -17,7 +84,7 @@ macro_rules! perf_level {
         )*
     ) => {
         {
-            let v = crate::settings::PERF_LEVEL;
+            let v = crate::settings::current_perf_level();
             let mut res = $fst;
             $(
                 if v >= crate::settings::PerfLevel::$level {
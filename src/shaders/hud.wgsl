@@ -0,0 +1,47 @@
+// Text overlay, each instance is a glyph cell with a 1 pixel margin around the 5x7 glyph
+
+struct HudSettings {
+    screen_size: vec2<f32>,
+    scale: f32,
+}
+
+@group(0) @binding(0)
+var<uniform> hud: HudSettings;
+
+const CELL_SIZE: vec2<f32> = vec2(6., 8.);
+
+struct GlyphInput {
+    @location(0) pos: vec2<f32>,
+    @location(1) bits: vec2<u32>,
+}
+
+struct GlyphOutput {
+    @builtin(position) pos: vec4<f32>,
+    @location(0) cell_pos: vec2<f32>,
+    @location(1) @interpolate(flat) bits: vec2<u32>,
+}
+
+@vertex
+fn vs_hud(@builtin(vertex_index) idx: u32, glyph: GlyphInput) -> GlyphOutput {
+    var corners = array(vec2(0., 0.), vec2(1., 0.), vec2(0., 1.), vec2(1., 0.), vec2(0., 1.), vec2(1., 1.));
+    let corner = corners[idx];
+    let pixel = glyph.pos + corner * CELL_SIZE * hud.scale;
+    let ndc = pixel / hud.screen_size * vec2(2., -2.) + vec2(-1., 1.);
+    var out: GlyphOutput;
+    out.pos = vec4(ndc, 0., 1.);
+    out.cell_pos = corner * CELL_SIZE;
+    out.bits = glyph.bits;
+    return out;
+}
+
+@fragment
+fn fs_hud(in: GlyphOutput) -> @location(0) vec4<f32> {
+    let cell = vec2<u32>(floor(in.cell_pos));
+    var lit = false;
+    if(cell.x < 5u && cell.y < 7u) {
+        let word = select(in.bits.y, in.bits.x, cell.y < 4u);
+        let row = (word >> (8u * (cell.y % 4u))) & 0x1Fu;
+        lit = ((row >> (4u - cell.x)) & 1u) == 1u;
+    }
+    return select(vec4(0., 0., 0., 0.5), vec4(1., 1., 1., 1.), lit);
+}
@@ -1,5 +1,5 @@
 
-const SPONGE_ITERATIONS: u32 = 5;
+// SPONGE_ITERATIONS is generated from the perf level, see render_registry::shaders
 fn is_on_sponge(puv: vec3<f32>) -> bool {
     var uv = abs(puv);
     uv = uv % 1.;
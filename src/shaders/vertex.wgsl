@@ -4,13 +4,13 @@ struct LocalGlobalMatrix {
 }
 
 struct Pos2Vertex {
-    @location(20) pos: vec2<f32>,
+    @location(5) pos: vec2<f32>,
 }
 struct Pos3Vertex {
-    @location(20) pos: vec3<f32>,
+    @location(5) pos: vec3<f32>,
 }
 struct TilePosVertex {
-    @location(21) pos: vec2<f32>,
+    @location(6) pos: vec2<f32>,
 }
 
 fn fvs_tri(
@@ -0,0 +1,14 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Writes tightly packed RGBA8 pixels as a binary PPM, the alpha channel is dropped
+pub fn write_ppm(path: &Path, width: u32, height: u32, rgba: &[u8]) -> std::io::Result<()> {
+    debug_assert_eq!(rgba.len(), (width * height * 4) as usize);
+    let mut file = BufWriter::new(File::create(path)?);
+    write!(file, "P6\n{width} {height}\n255\n")?;
+    for px in rgba.chunks_exact(4) {
+        file.write_all(&px[..3])?;
+    }
+    file.flush()
+}
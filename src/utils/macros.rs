@@ -8,7 +8,7 @@ macro_rules! array_key {
             $(,)?
         }
     ) => {
-        #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+        #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
         $vis enum $name {
             $($variant),*
         }
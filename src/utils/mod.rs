@@ -13,3 +13,6 @@ pub use iterators::*;
 
 pub mod algos;
 pub use algos::*;
+
+pub mod images;
+pub use images::*;
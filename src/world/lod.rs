@@ -0,0 +1,163 @@
+use crate::math::Transform;
+use crate::settings::PerfLevel;
+use crate::utils::Length;
+
+/// Time of the last frame against the time a frame should take, in seconds
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FrameBudget {
+    pub target: f32,
+    pub last: f32,
+}
+impl FrameBudget {
+    /// A frame always on time
+    pub fn on_time(target: f32) -> Self {
+        Self {
+            target,
+            last: target,
+        }
+    }
+    /// Above 1 when the frames are late
+    pub fn load(self) -> f32 {
+        self.last / self.target
+    }
+}
+
+/// How a world is seen from a camera
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LodDecision {
+    pub visible: bool,
+    /// Fraction of the frames updating the variators, in ]0, 1]
+    pub update_rate: f32,
+    /// Level of the tessellated prefabs, the global one when `None`
+    pub detail: Option<PerfLevel>,
+}
+impl LodDecision {
+    pub const FULL: Self = Self {
+        visible: true,
+        update_rate: 1.,
+        detail: None,
+    };
+    /// Before the first frame
+    pub const HIDDEN: Self = Self {
+        visible: false,
+        ..Self::FULL
+    };
+    /// Shown if any camera shows it, updated and detailed as for the closest one
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            visible: self.visible | other.visible,
+            update_rate: self.update_rate.max(other.update_rate),
+            detail: match (self.detail, other.detail) {
+                (Some(a), Some(b)) => Some(a.max(b)),
+                _ => None,
+            },
+        }
+    }
+}
+
+pub struct LodInput {
+    pub camera: Transform,
+    /// The world bounding box, if it has one
+    pub extents: Option<Transform>,
+    pub budget: FrameBudget,
+    /// The decision of the last frame, to avoid flickering at the thresholds
+    pub previous: LodDecision,
+}
+
+pub trait LodPolicy {
+    fn decide(&self, input: &LodInput) -> LodDecision;
+}
+
+/// Everything is always shown and updated
+pub struct NoLod;
+impl LodPolicy for NoLod {
+    fn decide(&self, _input: &LodInput) -> LodDecision {
+        LodDecision::FULL
+    }
+}
+
+/// Decides from the distance to the camera, in sizes of the world
+pub struct DistanceLod {
+    /// Closer than it, the world is updated each frame
+    pub fade_threshold: f32,
+    /// Further than it, the world is hidden
+    pub max_distance: f32,
+    /// Relative margin a threshold must be crossed by to change the decision
+    pub hysteresis: f32,
+    /// Prefab levels used from a distance, by increasing distance and decreasing detail
+    pub details: Vec<(f32, PerfLevel)>,
+}
+impl Default for DistanceLod {
+    fn default() -> Self {
+        Self {
+            fade_threshold: 5.,
+            max_distance: 100.,
+            hysteresis: 0.1,
+            details: Vec::new(),
+        }
+    }
+}
+impl DistanceLod {
+    /// Whether `dist` is under `threshold`, which moves away from the last side
+    fn under(&self, dist: f32, threshold: f32, was_under: bool) -> bool {
+        let margin = if was_under {
+            1. + self.hysteresis
+        } else {
+            1. - self.hysteresis
+        };
+        dist < threshold * margin
+    }
+}
+impl LodPolicy for DistanceLod {
+    fn decide(&self, input: &LodInput) -> LodDecision {
+        let Some(extents) = input.extents else {
+            return LodDecision::FULL;
+        };
+        let size_squared = 0.0f32
+            .max(extents.x().length_squared())
+            .max(extents.y().length_squared())
+            .max(extents.z().length_squared());
+        let dist =
+            ((input.camera.trans() - extents.trans()).length_squared() / size_squared).sqrt();
+
+        let previous = input.previous;
+        let mut detail = None;
+        for &(from, level) in &self.details {
+            if !self.under(dist, from, previous.detail.is_none_or(|d| d > level)) {
+                detail = Some(level);
+            }
+        }
+        // Late frames slow down the far worlds
+        let rate = self.fade_threshold / dist.max(self.fade_threshold);
+        LodDecision {
+            visible: self.under(dist, self.max_distance, previous.visible),
+            update_rate: if rate < 1. {
+                rate / input.budget.load().max(1.)
+            } else {
+                rate
+            },
+            detail,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hysteresis() {
+        let policy = DistanceLod::default();
+        let mut input = LodInput {
+            camera: Transform::ID,
+            extents: Some(Transform::from_transf(0., 0., 100.)),
+            budget: FrameBudget::on_time(1. / 60.),
+            previous: LodDecision::FULL,
+        };
+        assert!(policy.decide(&input).visible);
+        input.previous.visible = false;
+        assert!(!policy.decide(&input).visible);
+        input.extents = Some(Transform::from_transf(0., 0., 80.));
+        assert!(policy.decide(&input).visible);
+    }
+}
@@ -1,4 +1,8 @@
+pub mod lod;
+pub mod picking;
 pub mod primitives;
+pub mod scene_file;
+pub mod scene_graph;
 pub mod visuals;
 pub mod world;
 pub mod world_builder;
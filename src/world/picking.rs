@@ -0,0 +1,231 @@
+use crate::math::{Transform, Vec2, Vec3};
+use crate::render_registry::alloc::BufferAllocator;
+use crate::render_registry::materials::MaterialType;
+use crate::render_registry::vertex::VertexType;
+use crate::world::primitives::WorldPrimitive;
+use crate::world::primitives::camera::Camera;
+use crate::world::world::World;
+
+const MIN_DET: f32 = 1e-12;
+
+/// A half line, `dir` isn't normalized so the hit distances are in its unit
+#[derive(Clone, Copy, Debug)]
+pub struct Ray {
+    pub origin: Vec3,
+    pub dir: Vec3,
+}
+impl Ray {
+    /// The ray going through a point of the screen, in normalized device coordinates
+    pub fn from_camera(cam: &Camera, aspect_ratio: f32, ndc: Vec2) -> Self {
+        let tan = (cam.fov * 0.5).tan();
+        let view_dir = Vec3::new(ndc.x() * aspect_ratio * tan, ndc.y() * tan, 1.);
+        Self {
+            origin: cam.pos.trans(),
+            dir: cam.pos.tr_vec(view_dir),
+        }
+    }
+    pub fn at(self, t: f32) -> Vec3 {
+        self.origin + self.dir * t
+    }
+    fn to_local(self, tr: Transform) -> Option<Self> {
+        // Transform::inverse only asserts, a flattened instance just can't be hit
+        if tr.z().dot(tr.x().cross(tr.y())).abs() < MIN_DET {
+            return None;
+        }
+        let inv = tr.inverse();
+        Some(Self {
+            origin: inv.tr_point(self.origin),
+            dir: inv.tr_vec(self.dir),
+        })
+    }
+}
+
+/// The smallest positive root of a*t² + b*t + c
+fn first_root(a: f32, b: f32, c: f32, accept: impl Fn(f32) -> bool) -> Option<f32> {
+    let delta = b * b - 4. * a * c;
+    if a == 0. || delta < 0. {
+        return None;
+    }
+    let sq = delta.sqrt();
+    let (t0, t1) = ((-b - sq) / (2. * a), (-b + sq) / (2. * a));
+    let (t0, t1) = (t0.min(t1), t0.max(t1));
+    [t0, t1].into_iter().find(|&t| t > 0. && accept(t))
+}
+
+/// Unit sphere, like the circle prefab
+fn hit_sphere(ray: Ray) -> Option<f32> {
+    let (o, d) = (ray.origin, ray.dir);
+    first_root(d.dot(d), 2. * o.dot(d), o.dot(o) - 1., |_| true)
+}
+
+/// Cube from -1 to 1, like vs_cube
+fn hit_cube(ray: Ray) -> Option<f32> {
+    let (o, d) = (ray.origin.to_array(), ray.dir.to_array());
+    let (mut near, mut far) = (f32::NEG_INFINITY, f32::INFINITY);
+    for i in 0..3 {
+        if d[i] == 0. {
+            if o[i].abs() > 1. {
+                return None;
+            }
+            continue;
+        }
+        let (a, b) = ((-1. - o[i]) / d[i], (1. - o[i]) / d[i]);
+        near = near.max(a.min(b));
+        far = far.min(a.max(b));
+    }
+    if near > far || far <= 0. {
+        None
+    } else if near > 0. {
+        Some(near)
+    } else {
+        Some(far)
+    }
+}
+
+/// Open pipe of radius 1 going from z=0 to z=1, like the pipe prefab
+fn hit_pipe(ray: Ray) -> Option<f32> {
+    let (o, d) = (ray.origin, ray.dir);
+    first_root(
+        d.x() * d.x() + d.y() * d.y(),
+        2. * (o.x() * d.x() + o.y() * d.y()),
+        o.x() * o.x() + o.y() * o.y() - 1.,
+        |t| (0. ..=1.).contains(&ray.at(t).z()),
+    )
+}
+
+/// Möller–Trumbore, both faces
+fn hit_triangle(ray: Ray, [a, b, c]: [Vec3; 3]) -> Option<f32> {
+    let (e1, e2) = (b - a, c - a);
+    let p = ray.dir.cross(e2);
+    let det = e1.dot(p);
+    if det.abs() < MIN_DET {
+        return None;
+    }
+    let s = ray.origin - a;
+    let u = s.dot(p) / det;
+    let q = s.cross(e1);
+    let v = ray.dir.dot(q) / det;
+    if u < 0. || v < 0. || u + v > 1. {
+        return None;
+    }
+    Some(e2.dot(q) / det).filter(|&t| t > 0.)
+}
+
+/// What is under a pixel, the indices are the ones of the instance buffers and material stores
+#[derive(Clone, Copy, Debug)]
+pub struct Pick {
+    pub world: usize,
+    pub vertex_type: VertexType,
+    pub material_type: MaterialType,
+    pub instance: usize,
+    pub material: usize,
+    pub distance: f32,
+    pub point: Vec3,
+}
+impl Pick {
+    pub fn describe(&self) -> String {
+        format!(
+            "world {}, {} #{}, material {} #{} at {} ({:.2} away)",
+            self.world,
+            self.vertex_type.name(),
+            self.instance,
+            self.material_type.name(),
+            self.material,
+            self.point,
+            self.distance,
+        )
+    }
+}
+
+impl World {
+    /// Replays the directives on the CPU to find the closest instance hit by the ray.
+    /// Polynomials and tiled triangles aren't pickable.
+    pub fn pick(&self, world: usize, alloc: &BufferAllocator, ray: Ray) -> Option<Pick> {
+        let mut bufs = VertexType::ARRAY.map(|vty| {
+            let size = vty.instance_buffer_label().elt_size() as usize / 4;
+            MaterialType::ARRAY.map(|mty| vec![0u32; alloc.get_instance_count(vty, mty) * size])
+        });
+        self.redraw(
+            bufs.each_mut()
+                .map(|r| r.each_mut().map(|b| b.as_mut_slice())),
+        );
+
+        let transform = |i: u32| Transform::get(&self.stores, i as usize);
+        let point = |i: u32| Vec3::get(&self.stores, i as usize);
+        let mut best: Option<Pick> = None;
+        for vty in VertexType::ARRAY {
+            let size = vty.instance_buffer_label().elt_size() as usize / 4;
+            for mty in MaterialType::ARRAY {
+                let buf = &bufs[vty as usize][mty as usize];
+                for (instance, data) in buf.chunks_exact(size).enumerate() {
+                    let hit = match vty {
+                        VertexType::Sphere | VertexType::Cube | VertexType::Pipe => {
+                            let tr = transform(data[1]).tr_tr(transform(data[0]));
+                            ray.to_local(tr)
+                                .and_then(|local| match vty {
+                                    VertexType::Sphere => hit_sphere(local),
+                                    VertexType::Cube => hit_cube(local),
+                                    _ => hit_pipe(local),
+                                })
+                                .map(|t| (t, data[2]))
+                        }
+                        VertexType::Tri => {
+                            let global = transform(data[3]);
+                            let pts =
+                                [data[0], data[1], data[2]].map(|i| global.tr_point(point(i)));
+                            hit_triangle(ray, pts).map(|t| (t, data[4]))
+                        }
+                        VertexType::Poly4x4 | VertexType::TiledTri => None,
+                    };
+                    let Some((distance, material)) = hit else {
+                        continue;
+                    };
+                    if best.is_some_and(|b| b.distance <= distance) {
+                        continue;
+                    }
+                    best = Some(Pick {
+                        world,
+                        vertex_type: vty,
+                        material_type: mty,
+                        instance,
+                        material: material as usize,
+                        distance,
+                        point: ray.at(distance),
+                    });
+                }
+            }
+        }
+        best
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hits() {
+        let ray = Ray {
+            origin: Vec3::new(0., 0., -5.),
+            dir: Vec3::Z,
+        };
+        assert_eq!(hit_sphere(ray), Some(4.));
+        assert_eq!(hit_cube(ray), Some(4.));
+        assert_eq!(hit_pipe(ray), None);
+        let tri = [
+            Vec3::new(-1., -1., 0.),
+            Vec3::new(1., -1., 0.),
+            Vec3::new(0., 1., 0.),
+        ];
+        assert_eq!(hit_triangle(ray, tri), Some(5.));
+        let side = Ray {
+            origin: Vec3::new(-5., 0., 0.5),
+            dir: Vec3::X,
+        };
+        assert_eq!(hit_pipe(side), Some(4.));
+        let moved = side
+            .to_local(Transform::from_transv(Vec3::new(0., 0., 2.)))
+            .unwrap();
+        assert_eq!(hit_pipe(moved), None);
+    }
+}
@@ -0,0 +1,140 @@
+use crate::math::{Angle, Transform};
+use crate::utils::Length;
+use crate::world::primitives::camera::Camera;
+use crate::world::variators::variator::Variator;
+use crate::world::world::Worlds;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CameraSample {
+    pub time: f32,
+    pub camera: Camera,
+}
+
+/// Blends the two transforms, then makes the axes orthonormal again
+fn interpolate_transform(a: Transform, b: Transform, t: f32) -> Transform {
+    let mixed = a * (1. - t) + b * t;
+    let z = mixed.z().normalize();
+    let y = mixed.y() - z * mixed.y().dot(z);
+    let y = y.normalize();
+    Transform::from_cols(y.cross(z), y, z).with_trans(Transform::from_transv(mixed.trans()))
+}
+
+/// Cameras recorded over time, sampled with linear interpolation.
+/// As a variator, it follows the time of the scene
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CameraPath {
+    samples: Vec<CameraSample>,
+}
+impl CameraPath {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Samples have to be pushed in time order
+    pub fn push(&mut self, time: f32, camera: Camera) {
+        debug_assert!(self.samples.last().is_none_or(|s| s.time <= time));
+        self.samples.push(CameraSample { time, camera });
+    }
+    pub fn samples(&self) -> &[CameraSample] {
+        &self.samples
+    }
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+    pub fn duration(&self) -> f32 {
+        match (self.samples.first(), self.samples.last()) {
+            (Some(first), Some(last)) => last.time - first.time,
+            _ => 0.,
+        }
+    }
+    /// The camera at a time, clamped to the recorded range
+    pub fn sample(&self, time: f32) -> Option<Camera> {
+        let after = self.samples.partition_point(|s| s.time <= time);
+        let (a, b) = match after {
+            0 => return self.samples.first().map(|s| s.camera),
+            n if n == self.samples.len() => return self.samples.last().map(|s| s.camera),
+            n => (self.samples[n - 1], self.samples[n]),
+        };
+        let t = (time - a.time) / (b.time - a.time);
+        Some(Camera {
+            pos: interpolate_transform(a.camera.pos, b.camera.pos, t),
+            fov: a.camera.fov * (1. - t) + b.camera.fov * t,
+        })
+    }
+
+    /// One sample per line: the time, the fov in radians, then the 12 values of the transform
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let mut file = BufWriter::new(std::fs::File::create(path)?);
+        writeln!(file, "# time fov transform")?;
+        for sample in &self.samples {
+            write!(file, "{} {}", sample.time, sample.camera.fov.rad())?;
+            for v in sample.camera.pos.to_array() {
+                write!(file, " {v}")?;
+            }
+            writeln!(file)?;
+        }
+        file.flush()
+    }
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let file = BufReader::new(std::fs::File::open(path)?);
+        let mut camera_path = Self::new();
+        for (i, line) in file.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = |msg: &str| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("line {}: {msg}", i + 1),
+                )
+            };
+            let values = line
+                .split_whitespace()
+                .map(|v| v.parse::<f32>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|err| invalid(&err.to_string()))?;
+            let Ok([time, fov, transform @ ..]) = <[f32; 14]>::try_from(values) else {
+                return Err(invalid("expected 14 numbers"));
+            };
+            if camera_path.samples.last().is_some_and(|s| s.time > time) {
+                return Err(invalid("the samples are not in time order"));
+            }
+            camera_path.push(time, Camera {
+                pos: Transform::from_array(transform),
+                fov: Angle::from_rad(fov),
+            });
+        }
+        Ok(camera_path)
+    }
+}
+impl Variator for CameraPath {
+    type Item = Camera;
+    fn update(&self, worlds: &Worlds) -> Camera {
+        self.sample(worlds.settings.base_time).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::{ToAngle, rotate_y, trans};
+
+    #[test]
+    fn test_sample() {
+        let mut path = CameraPath::new();
+        path.push(1., Camera::default());
+        path.push(3., Camera {
+            pos: trans(2., 0., 0.) * rotate_y(90.0f32.deg()),
+            fov: 60.0f32.deg(),
+        });
+        assert_eq!(path.sample(0.), Some(Camera::default()));
+        let mid = path.sample(2.).unwrap();
+        assert!((mid.pos.trans() - trans(1., 0., 0.).trans()).length() < 1e-5);
+        assert!((mid.pos.z().length() - 1.).abs() < 1e-5);
+        assert!((mid.fov.deg() - 75.).abs() < 1e-3);
+        assert_eq!(path.sample(4.), path.samples().last().map(|s| s.camera));
+    }
+}
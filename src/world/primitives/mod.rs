@@ -6,9 +6,11 @@ use crate::render_registry::alloc::BufferAllocator;
 use crate::render_registry::storage_structs::AsStrorageStruct;
 use crate::utils::array_key;
 
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
+use std::ops::Range;
 
 pub mod camera;
+pub mod camera_path;
 pub mod color;
 pub mod reference;
 
@@ -30,6 +32,8 @@ macro_rules! make_primitive_system {
             $(
                 $snake_name: Vec<Cell<$prim_ty>>,
             )*
+            /// The spans of each store changed since they were last uploaded
+            dirty: [RefCell<Vec<Range<usize>>>; StoreLabel::COUNT],
         }
         impl Default for PrimitiveStoresHolder {
             fn default() -> Self {
@@ -37,6 +41,7 @@ macro_rules! make_primitive_system {
                     $(
                         $snake_name: Vec::new(),
                     )*
+                    dirty: Default::default(),
                 }
             }
         }
@@ -68,6 +73,7 @@ macro_rules! make_primitive_system {
                     $(
                         $snake_name: vec![Cell::new(<$prim_ty>::default()); self.$snake_name],
                     )*
+                    dirty: Default::default(),
                 }
             }
         }
@@ -83,7 +89,12 @@ macro_rules! make_primitive_system {
                     stores.$snake_name[index].get()
                 }
                 fn set(stores: &PrimitiveStoresHolder, index: usize, value: Self) {
-                    stores.$snake_name[index].set(value);
+                    let _old = stores.$snake_name[index].replace(value);
+                    $(
+                        if _old != value {
+                            stores.mark_dirty(StoreLabel::$store_name, index);
+                        }
+                    )?
                 }
                 fn sets<const N: usize>(stores: &PrimitiveStoresHolder, index: usize, values: [Self; N]) {
                     for i in 0..values.len() {
@@ -101,20 +112,31 @@ macro_rules! make_primitive_system {
             }
         );
         impl StoreLabel {
-            pub fn write(self, buf: &mut [u32], stores: &PrimitiveStoresHolder) {
+            /// Writes the entries of the span at the start of `buf`
+            pub fn write(self, buf: &mut [u32], stores: &PrimitiveStoresHolder, span: Range<usize>) {
                 match self {
                     $(
                         $(
                             Self::$store_name => {
                                 let arr: &mut [<$prim_ty as AsStrorageStruct>::S] = bytemuck::cast_slice_mut(buf);
-                                for i in 0..stores.$snake_name.len() {
-                                    arr[i] = stores.$snake_name[i].get().as_strorage_struct();
+                                for (i, idx) in span.enumerate() {
+                                    arr[i] = stores.$snake_name[idx].get().as_strorage_struct();
                                 }
                             }
                         )?
                     )*
                 }
             }
+            /// The number of entries of the store
+            pub fn len(self, stores: &PrimitiveStoresHolder) -> usize {
+                match self {
+                    $(
+                        $(
+                            Self::$store_name => stores.$snake_name.len(),
+                        )?
+                    )*
+                }
+            }
         }
     };
 }
@@ -136,10 +158,37 @@ make_primitive_system!(
     polynomial4x4: Polynomial4x4 {Poly4x4};
 );
 
+/// Number of unchanged elements below which two dirty spans are uploaded together
+const MERGED_GAP: usize = 64;
+
 impl PrimitiveStoresHolder {
     pub fn nb_cameras(&self) -> usize {
         self.camera.len()
     }
+    /// The values are usually set in order, so the index mostly extends the last span
+    fn mark_dirty(&self, label: StoreLabel, index: usize) {
+        let mut spans = self.dirty[label as usize].borrow_mut();
+        match spans.last_mut() {
+            Some(last) if last.start <= index && index <= last.end => {
+                last.end = last.end.max(index + 1)
+            }
+            _ => spans.push(index..index + 1),
+        }
+    }
+    /// The sorted and disjoint spans changed since the last call.
+    /// Spans closer than `MERGED_GAP` are merged, to upload them in a single write
+    pub fn take_dirty(&self, label: StoreLabel) -> Vec<Range<usize>> {
+        let mut spans = self.dirty[label as usize].take();
+        spans.sort_unstable_by_key(|span| span.start);
+        let mut merged: Vec<Range<usize>> = Vec::with_capacity(spans.len());
+        for span in spans {
+            match merged.last_mut() {
+                Some(last) if span.start < last.end + MERGED_GAP => last.end = last.end.max(span.end),
+                _ => merged.push(span),
+            }
+        }
+        merged
+    }
 }
 
 impl StoreLabel {
@@ -172,3 +221,26 @@ impl StoreLabel {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::vec3;
+
+    #[test]
+    fn dirty_spans() {
+        let mut tracker = PrimitivesAllocationTracker::default();
+        Vec3::alloc(&mut tracker, 300);
+        let stores = tracker.to_store_holder();
+        for idx in [0, 1, 2, 7, 1, 3, 200, 270, 265] {
+            Vec3::set(&stores, idx, vec3(1., 2., 3.));
+        }
+        Vec3::set(&stores, 100, Vec3::default());
+        assert_eq!(
+            stores.take_dirty(StoreLabel::Vec3),
+            vec![0..8, 200..201, 265..271]
+        );
+        Vec3::set(&stores, 7, vec3(1., 2., 3.));
+        assert!(stores.take_dirty(StoreLabel::Vec3).is_empty());
+    }
+}
@@ -0,0 +1,240 @@
+use std::f32::consts::PI;
+use std::rc::Rc;
+
+use crate::math::{Angle, Transform, Vec3, vec3};
+use crate::world::primitives::color::Color;
+use crate::world::scene_graph::Node;
+use crate::world::variators::variator::Variator;
+use crate::world::world::Worlds;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ty {
+    Float,
+    Vec3,
+    Transform,
+    Color,
+}
+impl Ty {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Float => "number",
+            Self::Vec3 => "vector",
+            Self::Transform => "transform",
+            Self::Color => "color",
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Value {
+    Float(f32),
+    Vec3(Vec3),
+    Transform(Transform),
+    Color(Color),
+}
+impl Value {
+    pub fn ty(&self) -> Ty {
+        match self {
+            Self::Float(_) => Ty::Float,
+            Self::Vec3(_) => Ty::Vec3,
+            Self::Transform(_) => Ty::Transform,
+            Self::Color(_) => Ty::Color,
+        }
+    }
+}
+
+/// Unwraps a value whose type was checked while parsing
+pub trait FromValue: Sized {
+    fn from_value(value: Value) -> Self;
+}
+macro_rules! from_value {
+    ($($variant: ident: $t: ty),*) => {$(
+        impl FromValue for $t {
+            fn from_value(value: Value) -> Self {
+                match value {
+                    Value::$variant(v) => v,
+                    _ => unreachable!("type checked"),
+                }
+            }
+        }
+    )*};
+}
+from_value!(Float: f32, Vec3: Vec3, Transform: Transform, Color: Color);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+impl BinOp {
+    pub fn from_symbol(c: char) -> Option<Self> {
+        match c {
+            '+' => Some(Self::Add),
+            '-' => Some(Self::Sub),
+            '*' => Some(Self::Mul),
+            '/' => Some(Self::Div),
+            _ => None,
+        }
+    }
+    pub fn output(self, a: Ty, b: Ty) -> Option<Ty> {
+        match (self, a, b) {
+            (_, Ty::Float, Ty::Float) => Some(Ty::Float),
+            (Self::Add | Self::Sub, Ty::Vec3, Ty::Vec3)
+            | (Self::Mul | Self::Div, Ty::Vec3, Ty::Float)
+            | (Self::Mul, Ty::Float, Ty::Vec3) => Some(Ty::Vec3),
+            (Self::Mul, Ty::Transform, Ty::Transform)
+            | (Self::Add | Self::Sub, Ty::Transform, Ty::Vec3) => Some(Ty::Transform),
+            (Self::Add, Ty::Color, Ty::Color) | (Self::Mul, Ty::Color, Ty::Float) => {
+                Some(Ty::Color)
+            }
+            _ => None,
+        }
+    }
+    fn apply(self, a: Value, b: Value) -> Value {
+        match (self, a, b) {
+            (Self::Add, Value::Float(a), Value::Float(b)) => Value::Float(a + b),
+            (Self::Sub, Value::Float(a), Value::Float(b)) => Value::Float(a - b),
+            (Self::Mul, Value::Float(a), Value::Float(b)) => Value::Float(a * b),
+            (Self::Div, Value::Float(a), Value::Float(b)) => Value::Float(a / b),
+            (Self::Add, Value::Vec3(a), Value::Vec3(b)) => Value::Vec3(a + b),
+            (Self::Sub, Value::Vec3(a), Value::Vec3(b)) => Value::Vec3(a - b),
+            (Self::Mul, Value::Vec3(a), Value::Float(b))
+            | (Self::Mul, Value::Float(b), Value::Vec3(a)) => Value::Vec3(a * b),
+            (Self::Div, Value::Vec3(a), Value::Float(b)) => Value::Vec3(a / b),
+            (Self::Mul, Value::Transform(a), Value::Transform(b)) => Value::Transform(a * b),
+            (Self::Add, Value::Transform(a), Value::Vec3(b)) => Value::Transform(a + b),
+            (Self::Sub, Value::Transform(a), Value::Vec3(b)) => Value::Transform(a - b),
+            (Self::Add, Value::Color(a), Value::Color(b)) => Value::Color(a + b),
+            (Self::Mul, Value::Color(a), Value::Float(b)) => Value::Color(a * b),
+            _ => unreachable!("type checked"),
+        }
+    }
+}
+
+pub struct Function {
+    pub name: &'static str,
+    pub args: &'static [Ty],
+    pub output: Ty,
+    pub eval: fn(&[Value]) -> Value,
+}
+
+macro_rules! functions {
+    (
+        $($name: ident ($($arg: ident: $ty: ident),*) -> $out: ident $body: block)*
+    ) => {
+        /// The functions usable in expressions, the same name can take different arguments
+        pub const FUNCTIONS: &[Function] = &[$(
+            Function {
+                name: stringify!($name),
+                args: &[$(Ty::$ty),*],
+                output: Ty::$out,
+                eval: |args| {
+                    #[allow(unused_variables, irrefutable_let_patterns)]
+                    let [$($arg),*] = args else { unreachable!("type checked") };
+                    $(let $arg = <$ty as FromValue>::from_value(*$arg);)*
+                    Value::$out($body)
+                },
+            },
+        )*];
+    };
+}
+type Float = f32;
+functions!(
+    sin(x: Float) -> Float { x.sin() }
+    cos(x: Float) -> Float { x.cos() }
+    vec(x: Float, y: Float, z: Float) -> Vec3 { vec3(x, y, z) }
+    translate(x: Float, y: Float, z: Float) -> Transform { Transform::from_transf(x, y, z) }
+    translate(v: Vec3) -> Transform { Transform::from_transv(v) }
+    scale(s: Float) -> Transform { Transform::from_scalef(s, s, s) }
+    scale(x: Float, y: Float, z: Float) -> Transform { Transform::from_scalef(x, y, z) }
+    rotate_x(deg: Float) -> Transform { Transform::from_rotate_x(Angle::from_deg(deg)) }
+    rotate_y(deg: Float) -> Transform { Transform::from_rotate_y(Angle::from_deg(deg)) }
+    rotate_z(deg: Float) -> Transform { Transform::from_rotate_z(Angle::from_deg(deg)) }
+    look_at(v: Vec3) -> Transform { Transform::from_z_looking_at(v) }
+    rgb(r: Float, g: Float, b: Float) -> Color { Color::from_rgbf(r, g, b) }
+    oklch(l: Float, c: Float, h: Float) -> Color { Color::from_oklchf(l, c, h * PI / 180.) }
+);
+
+/// The names known in every world, `time` excepted
+pub fn constant(name: &str) -> Option<Value> {
+    Some(match name {
+        "pi" => Value::Float(PI),
+        "identity" => Value::Transform(Transform::ID),
+        "white" => Value::Color(Color::WHITE),
+        "black" => Value::Color(Color::BLACK),
+        "red" => Value::Color(Color::RED),
+        "green" => Value::Color(Color::GREEN),
+        "blue" => Value::Color(Color::BLUE),
+        "yellow" => Value::Color(Color::YELLOW),
+        _ => return None,
+    })
+}
+
+/// A type checked expression, the constant parts are already computed
+pub enum Expr {
+    Const(Value),
+    Time,
+    Node(Node),
+    /// A named value, computed again where it's used
+    Named(Rc<Expr>),
+    Neg(Box<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+    Call(&'static Function, Vec<Expr>),
+}
+impl Expr {
+    pub fn eval(&self, worlds: &Worlds) -> Value {
+        match self {
+            Self::Const(value) => *value,
+            Self::Time => Value::Float(worlds.settings.base_time),
+            Self::Node(node) => Value::Transform(node.global().update(worlds)),
+            Self::Named(expr) => expr.eval(worlds),
+            Self::Neg(expr) => neg(expr.eval(worlds)),
+            Self::Binary(op, a, b) => op.apply(a.eval(worlds), b.eval(worlds)),
+            Self::Call(function, args) => {
+                let args = args.iter().map(|arg| arg.eval(worlds)).collect::<Vec<_>>();
+                (function.eval)(&args)
+            }
+        }
+    }
+    /// Computes the constant expressions at once
+    pub fn fold(self) -> Self {
+        let value = match &self {
+            Self::Neg(expr) => expr.as_const().map(neg),
+            Self::Binary(op, a, b) => a.as_const().zip(b.as_const()).map(|(a, b)| op.apply(a, b)),
+            Self::Call(function, args) => args
+                .iter()
+                .map(Self::as_const)
+                .collect::<Option<Vec<_>>>()
+                .map(|args| (function.eval)(&args)),
+            _ => None,
+        };
+        value.map_or(self, Self::Const)
+    }
+    /// Nodes and constants are copied, the rest is shared
+    pub fn named(expr: &Rc<Expr>) -> Self {
+        match **expr {
+            Self::Const(value) => Self::Const(value),
+            Self::Node(node) => Self::Node(node),
+            _ => Self::Named(expr.clone()),
+        }
+    }
+    fn as_const(&self) -> Option<Value> {
+        match self {
+            Self::Const(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+pub fn neg_output(ty: Ty) -> Option<Ty> {
+    matches!(ty, Ty::Float | Ty::Vec3).then_some(ty)
+}
+fn neg(value: Value) -> Value {
+    match value {
+        Value::Float(v) => Value::Float(-v),
+        Value::Vec3(v) => Value::Vec3(-v),
+        _ => unreachable!("type checked"),
+    }
+}
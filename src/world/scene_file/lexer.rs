@@ -0,0 +1,92 @@
+use super::SceneError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pos {
+    pub line: usize,
+    pub col: usize,
+}
+impl Pos {
+    pub fn error(self, message: impl Into<String>) -> SceneError {
+        SceneError {
+            line: self.line,
+            col: self.col,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    Ident(String),
+    Number(f32),
+    Symbol(char),
+    Newline,
+    End,
+}
+impl Token {
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Ident(name) => format!("`{name}`"),
+            Self::Number(value) => format!("`{value}`"),
+            Self::Symbol(c) => format!("`{c}`"),
+            Self::Newline => "end of line".to_string(),
+            Self::End => "end of file".to_string(),
+        }
+    }
+}
+
+const SYMBOLS: &str = "(){},=+-*/";
+
+/// Splits the source in tokens, `#` starts a comment until the end of the line
+pub fn tokenize(src: &str) -> Result<Vec<(Token, Pos)>, SceneError> {
+    let mut tokens = Vec::new();
+    for (i, line) in src.lines().enumerate() {
+        let mut chars = line.char_indices().peekable();
+        while let Some((start, c)) = chars.next() {
+            let pos = Pos {
+                line: i + 1,
+                col: line[..start].chars().count() + 1,
+            };
+            let mut end = start + c.len_utf8();
+            let mut take_while = |f: fn(char) -> bool| {
+                while let Some(&(idx, c)) = chars.peek() {
+                    if !f(c) {
+                        break;
+                    }
+                    end = idx + c.len_utf8();
+                    chars.next();
+                }
+                &line[start..end]
+            };
+            let token = match c {
+                '#' => break,
+                c if c.is_whitespace() => continue,
+                c if c.is_ascii_digit() || c == '.' => {
+                    let text = take_while(|c| c.is_ascii_digit() || c == '.');
+                    Token::Number(
+                        text.parse()
+                            .map_err(|_| pos.error(format!("invalid number `{text}`")))?,
+                    )
+                }
+                c if c.is_alphabetic() || c == '_' => {
+                    Token::Ident(take_while(|c| c.is_alphanumeric() || c == '_').to_string())
+                }
+                c if SYMBOLS.contains(c) => Token::Symbol(c),
+                c => return Err(pos.error(format!("unexpected character `{c}`"))),
+            };
+            tokens.push((token, pos));
+        }
+        tokens.push((
+            Token::Newline,
+            Pos {
+                line: i + 1,
+                col: line.chars().count() + 1,
+            },
+        ));
+    }
+    let last = tokens
+        .last()
+        .map_or(Pos { line: 1, col: 1 }, |(_, pos)| *pos);
+    tokens.push((Token::End, last));
+    Ok(tokens)
+}
@@ -0,0 +1,94 @@
+//! Scenes written as text, loaded into a `WorldsBuilder`
+//!
+//! ```text
+//! # Comments start with `#`
+//! world 1 {
+//!     let spin = rotate_y(time * 20)
+//!     node sun = spin
+//!     node moon in sun = translate(3, 0, 0) * scale(0.3)
+//!     color oklch(0.8, 0.2, 90)
+//!     sphere sun
+//!     color rgb(0.5, 0.5, 0.6)
+//!     sphere moon
+//!     global moon
+//!     sponge red, blue
+//!     cube translate(0, 2, 0) * scale(0.5)
+//!     bounding_box scale(4)
+//! }
+//! ```
+//!
+//! Each world has a layer and a list of statements, one per line:
+//! - `let <name> = <expr>` names a value, computed again where it is used
+//! - `node <name> [in <parent>] = <transform>` adds a node of the scene graph
+//! - `color`, `border <color>` and `sponge <color>, <color>` set the material
+//! - `global <transform>` sets the transform applied to the next shapes
+//! - `sphere`, `cube` and `pipe <transform>` draw the shape transformed
+//! - `triangle <a>, <b>, <c>` and `tiled_triangle <a>, <b>, <c>, <tile transform>`
+//! - `surface <16 control points>` draws a bezier surface, row by row
+//! - `bounding_box <transform>` sets the extents of the world
+//!
+//! Expressions use numbers, `time`, the named values, `+ - * /` and the functions
+//! of [`expr::FUNCTIONS`]. Angles are in degrees.
+
+mod expr;
+mod lexer;
+mod parser;
+
+use std::fmt::{Display, Formatter};
+
+use crate::world::world_builder::WorldsBuilder;
+
+/// An error in a scene file, lines and columns start at 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SceneError {
+    pub line: usize,
+    pub col: usize,
+    pub message: String,
+}
+impl Display for SceneError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.col, self.message)
+    }
+}
+impl std::error::Error for SceneError {}
+
+pub fn load_scene(src: &str) -> Result<WorldsBuilder, SceneError> {
+    parser::Parser::new(src)?.parse_scene()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(src: &str) -> (usize, usize) {
+        let err = load_scene(src).err().expect("the scene should be invalid");
+        (err.line, err.col)
+    }
+
+    #[test]
+    fn scenes() {
+        let src = "
+            # Two worlds
+            world 2 {
+                let spin = rotate_y(time * 20)
+                node sun = spin * scale(2)
+                node moon in sun = translate(3, 0, 0)
+                color oklch(0.8, 0.2, 90) * 0.5
+                sphere moon
+                triangle vec(0, 0, 0), -vec(1, 0, 0), vec(0, 1, 0) / 2
+            }
+            world 0 {
+                cube identity + vec(0, 1, 0)
+            }
+        ";
+        load_scene(src).unwrap();
+
+        assert_eq!(error(""), (1, 1));
+        assert_eq!(error("world 1 {\n  sphere 1\n}"), (2, 10));
+        assert_eq!(error("world 1 {\n  let a = scale(1, 2)\n}"), (2, 11));
+        assert_eq!(error("world 1 {\n  let a = red * vec(1, 2, 3)\n}"), (2, 15));
+        assert_eq!(error("world 1 {\n  cube moon\n"), (2, 8));
+        assert_eq!(error("world 1 {\n  sphere identity\n"), (2, 18));
+        assert_eq!(error("world 100000000 {\n}"), (1, 7));
+    }
+}
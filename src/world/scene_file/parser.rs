@@ -0,0 +1,441 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::math::{Polynomial, Transform, Vec3};
+use crate::world::primitives::WorldPrimitive;
+use crate::world::primitives::color::Color;
+use crate::world::scene_graph::Parent;
+use crate::world::variators::references::Ref;
+use crate::world::visuals::{Border, Cube, Pipe, Sphere, Sponge, Tiled, Triangle};
+use crate::world::world::Worlds;
+use crate::world::world_builder::{WorldBuilder, WorldsBuilder};
+
+use super::SceneError;
+use super::expr::{BinOp, Expr, FUNCTIONS, FromValue, Ty, Value, constant, neg_output};
+use super::lexer::{Pos, Token, tokenize};
+
+/// Each layer up to the highest one gets a list of worlds
+const MAX_LAYER: usize = 255;
+
+/// The statements drawing something, with the types of their arguments
+const DIRECTIVES: &[(&str, &[Ty])] = &[
+    ("color", &[Ty::Color]),
+    ("border", &[Ty::Color]),
+    ("sponge", &[Ty::Color, Ty::Color]),
+    ("global", &[Ty::Transform]),
+    ("sphere", &[Ty::Transform]),
+    ("cube", &[Ty::Transform]),
+    ("pipe", &[Ty::Transform]),
+    ("triangle", &[Ty::Vec3; 3]),
+    (
+        "tiled_triangle",
+        &[Ty::Vec3, Ty::Vec3, Ty::Vec3, Ty::Transform],
+    ),
+    ("surface", &[Ty::Vec3; 16]),
+    ("bounding_box", &[Ty::Transform]),
+];
+
+fn store<T: FromValue + WorldPrimitive + 'static>(world: &mut WorldBuilder, expr: Expr) -> Ref<T> {
+    world.push(move |worlds: &Worlds| T::from_value(expr.eval(worlds)))
+}
+/// Nodes are used as they are
+fn store_transform(world: &mut WorldBuilder, expr: Expr) -> Ref<Transform> {
+    match expr {
+        Expr::Node(node) => node.global(),
+        expr => store(world, expr),
+    }
+}
+
+/// The named values of a world, with their types
+type Scope = HashMap<String, (Rc<Expr>, Ty)>;
+
+pub struct Parser {
+    tokens: Vec<(Token, Pos)>,
+    next: usize,
+}
+impl Parser {
+    pub fn new(src: &str) -> Result<Self, SceneError> {
+        Ok(Self {
+            tokens: tokenize(src)?,
+            next: 0,
+        })
+    }
+    fn peek(&self) -> &Token {
+        &self.tokens[self.next].0
+    }
+    fn pos(&self) -> Pos {
+        self.tokens[self.next].1
+    }
+    fn bump(&mut self) -> (Token, Pos) {
+        let token = self.tokens[self.next].clone();
+        if token.0 != Token::End {
+            self.next += 1;
+        }
+        token
+    }
+    fn unexpected(&self, expected: &str) -> SceneError {
+        self.pos().error(format!(
+            "expected {expected}, found {}",
+            self.peek().describe()
+        ))
+    }
+    fn eat_symbol(&mut self, c: char) -> bool {
+        let found = *self.peek() == Token::Symbol(c);
+        if found {
+            self.bump();
+        }
+        found
+    }
+    fn expect_symbol(&mut self, c: char) -> Result<(), SceneError> {
+        if self.eat_symbol(c) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("`{c}`")))
+        }
+    }
+    fn expect_ident(&mut self, expected: &str) -> Result<(String, Pos), SceneError> {
+        let (Token::Ident(name), pos) = self.tokens[self.next].clone() else {
+            return Err(self.unexpected(expected));
+        };
+        self.bump();
+        Ok((name, pos))
+    }
+    fn expect_line_end(&mut self) -> Result<(), SceneError> {
+        match self.peek() {
+            Token::Newline => {
+                self.bump();
+                Ok(())
+            }
+            Token::End => Ok(()),
+            _ => Err(self.unexpected("end of line")),
+        }
+    }
+    fn skip_newlines(&mut self) {
+        while *self.peek() == Token::Newline {
+            self.bump();
+        }
+    }
+
+    pub fn parse_scene(mut self) -> Result<WorldsBuilder, SceneError> {
+        let mut worlds = WorldsBuilder::default();
+        let mut empty = true;
+        loop {
+            self.skip_newlines();
+            if *self.peek() == Token::End {
+                break;
+            }
+            worlds = self.parse_world(worlds)?;
+            empty = false;
+        }
+        if empty {
+            return Err(self.pos().error("the scene has no world"));
+        }
+        Ok(worlds)
+    }
+    /// `world <layer> { <statements> }`
+    fn parse_world(&mut self, worlds: WorldsBuilder) -> Result<WorldsBuilder, SceneError> {
+        let (keyword, pos) = self.expect_ident("`world`")?;
+        if keyword != "world" {
+            return Err(pos.error(format!("expected `world`, found `{keyword}`")));
+        }
+        let layer = match self.bump() {
+            (Token::Number(layer), pos) if layer > MAX_LAYER as f32 => {
+                return Err(pos.error(format!("the layer can't be above {MAX_LAYER}")));
+            }
+            (Token::Number(layer), _) if layer >= 0. && layer.fract() == 0. => layer as usize,
+            (_, pos) => return Err(pos.error("expected the layer of the world, like `world 1 {`")),
+        };
+        self.expect_symbol('{')?;
+        self.expect_line_end()?;
+
+        let mut world = worlds.add_world(layer);
+        // Defaults, until the first `global` and material
+        let global = world.push(Transform::ID);
+        let material = world.push(Color::WHITE);
+        world.push_visual((global, material));
+
+        let mut scope = Scope::new();
+        loop {
+            self.skip_newlines();
+            if self.eat_symbol('}') {
+                break;
+            }
+            if *self.peek() == Token::End {
+                return Err(self.unexpected("`}`"));
+            }
+            self.parse_statement(&mut world, &mut scope)?;
+            self.expect_line_end()?;
+        }
+        self.expect_line_end()?;
+        Ok(world.finalize())
+    }
+    fn parse_statement(
+        &mut self,
+        world: &mut WorldBuilder,
+        scope: &mut Scope,
+    ) -> Result<(), SceneError> {
+        let (keyword, pos) = self.expect_ident("a statement")?;
+        match keyword.as_str() {
+            "let" => {
+                let (name, _) = self.parse_new_name(scope)?;
+                self.expect_symbol('=')?;
+                let (expr, ty, _) = self.parse_expr(scope)?;
+                scope.insert(name, (Rc::new(expr), ty));
+            }
+            "node" => {
+                let (name, _) = self.parse_new_name(scope)?;
+                let parent = match self.peek() {
+                    Token::Ident(word) if word == "in" => {
+                        self.bump();
+                        let (parent, pos) = self.expect_ident("the parent node")?;
+                        match scope.get(&parent) {
+                            Some((expr, Ty::Transform)) => Some(Expr::named(expr)),
+                            Some((_, ty)) => {
+                                return Err(pos.error(format!(
+                                    "`{parent}` is a {}, the parent must be a node or a transform",
+                                    ty.name()
+                                )));
+                            }
+                            None => return Err(pos.error(format!("unknown name `{parent}`"))),
+                        }
+                    }
+                    _ => None,
+                };
+                self.expect_symbol('=')?;
+                let local = self.parse_typed(scope, Ty::Transform)?;
+                // Other transforms are computed with the node, so they are never late
+                let (parent, local) = match parent {
+                    None => (Parent::Root, local),
+                    Some(Expr::Node(node)) => (Parent::Node(node), local),
+                    Some(parent) => (
+                        Parent::Root,
+                        Expr::Binary(BinOp::Mul, Box::new(parent), Box::new(local)).fold(),
+                    ),
+                };
+                let node = world.push_node(parent, move |worlds: &Worlds| {
+                    Transform::from_value(local.eval(worlds))
+                });
+                scope.insert(name, (Rc::new(Expr::Node(node)), Ty::Transform));
+            }
+            directive => {
+                let Some((_, types)) = DIRECTIVES.iter().find(|(name, _)| *name == directive)
+                else {
+                    return Err(pos.error(format!("unknown statement `{directive}`")));
+                };
+                let mut args = Vec::new();
+                for (i, ty) in types.iter().enumerate() {
+                    if i > 0 {
+                        self.expect_symbol(',')?;
+                    }
+                    args.push(self.parse_typed(scope, *ty)?);
+                }
+                self.push_directive(world, directive, args);
+            }
+        }
+        Ok(())
+    }
+    fn parse_new_name(&mut self, scope: &Scope) -> Result<(String, Pos), SceneError> {
+        let (name, pos) = self.expect_ident("a name")?;
+        if scope.contains_key(&name) || constant(&name).is_some() || name == "time" {
+            return Err(pos.error(format!("`{name}` is already defined")));
+        }
+        Ok((name, pos))
+    }
+    fn push_directive(&self, world: &mut WorldBuilder, directive: &str, args: Vec<Expr>) {
+        let mut args = args.into_iter();
+        let mut next = || args.next().expect("checked by the directive types");
+        match directive {
+            "color" => {
+                let color: Ref<Color> = store(world, next());
+                world.push_visual(color)
+            }
+            "border" => {
+                let color = store(world, next());
+                world.push_visual(Border(color))
+            }
+            "sponge" => {
+                let (a, b) = (next(), next());
+                let colors = world.push(move |worlds: &Worlds| {
+                    (
+                        Color::from_value(a.eval(worlds)),
+                        Color::from_value(b.eval(worlds)),
+                    )
+                });
+                world.push_visual(Sponge(colors))
+            }
+            "global" => {
+                let global = store_transform(world, next());
+                world.push_visual(global)
+            }
+            "sphere" => {
+                let tr = store_transform(world, next());
+                world.push_visual(Sphere(tr))
+            }
+            "cube" => {
+                let tr = store_transform(world, next());
+                world.push_visual(Cube(tr))
+            }
+            "pipe" => {
+                let tr = store_transform(world, next());
+                world.push_visual(Pipe(tr))
+            }
+            "triangle" => {
+                let pts: [Ref<Vec3>; 3] = std::array::from_fn(|_| store(world, next()));
+                world.push_visual(Triangle(pts[0], pts[1], pts[2]))
+            }
+            "tiled_triangle" => {
+                let pts: [Ref<Vec3>; 3] = std::array::from_fn(|_| store(world, next()));
+                let tile = store_transform(world, next());
+                world.push_visual(Tiled(Triangle(pts[0], pts[1], pts[2]), tile))
+            }
+            "surface" => {
+                let pts: [[Expr; 4]; 4] = std::array::from_fn(|_| std::array::from_fn(|_| next()));
+                let surface = world.push(move |worlds: &Worlds| {
+                    Polynomial::new_bezier_surface(
+                        pts.each_ref()
+                            .map(|row| row.each_ref().map(|pt| Vec3::from_value(pt.eval(worlds)))),
+                    )
+                });
+                world.push_visual(surface)
+            }
+            "bounding_box" => {
+                let expr = next();
+                world.set_bounding_box(move |worlds: &Worlds| {
+                    Transform::from_value(expr.eval(worlds))
+                })
+            }
+            _ => unreachable!("unknown directives are rejected while parsing"),
+        }
+    }
+
+    fn parse_typed(&mut self, scope: &Scope, expected: Ty) -> Result<Expr, SceneError> {
+        let (expr, ty, pos) = self.parse_expr(scope)?;
+        if ty != expected {
+            return Err(pos.error(format!(
+                "expected a {}, found a {}",
+                expected.name(),
+                ty.name()
+            )));
+        }
+        Ok(expr)
+    }
+    /// Sums of products
+    fn parse_expr(&mut self, scope: &Scope) -> Result<(Expr, Ty, Pos), SceneError> {
+        self.parse_binary(scope, &['+', '-'], |p, scope| {
+            p.parse_binary(scope, &['*', '/'], Self::parse_unary)
+        })
+    }
+    fn parse_binary(
+        &mut self,
+        scope: &Scope,
+        symbols: &[char],
+        operand: impl Fn(&mut Self, &Scope) -> Result<(Expr, Ty, Pos), SceneError>,
+    ) -> Result<(Expr, Ty, Pos), SceneError> {
+        let (mut expr, mut ty, start) = operand(self, scope)?;
+        while let Token::Symbol(c) = *self.peek() {
+            if !symbols.contains(&c) {
+                break;
+            }
+            let (_, pos) = self.bump();
+            let op = BinOp::from_symbol(c).unwrap();
+            let (rhs, rhs_ty, _) = operand(self, scope)?;
+            ty = op.output(ty, rhs_ty).ok_or_else(|| {
+                pos.error(format!(
+                    "can't use `{c}` between a {} and a {}",
+                    ty.name(),
+                    rhs_ty.name()
+                ))
+            })?;
+            expr = Expr::Binary(op, Box::new(expr), Box::new(rhs)).fold();
+        }
+        Ok((expr, ty, start))
+    }
+    fn parse_unary(&mut self, scope: &Scope) -> Result<(Expr, Ty, Pos), SceneError> {
+        if *self.peek() == Token::Symbol('-') {
+            let (_, pos) = self.bump();
+            let (expr, ty, _) = self.parse_unary(scope)?;
+            let ty =
+                neg_output(ty).ok_or_else(|| pos.error(format!("can't negate a {}", ty.name())))?;
+            return Ok((Expr::Neg(Box::new(expr)).fold(), ty, pos));
+        }
+        self.parse_primary(scope)
+    }
+    fn parse_primary(&mut self, scope: &Scope) -> Result<(Expr, Ty, Pos), SceneError> {
+        let (token, pos) = self.tokens[self.next].clone();
+        if !matches!(
+            token,
+            Token::Number(_) | Token::Ident(_) | Token::Symbol('(')
+        ) {
+            return Err(self.unexpected("an expression"));
+        }
+        self.bump();
+        match token {
+            Token::Number(value) => Ok((Expr::Const(Value::Float(value)), Ty::Float, pos)),
+            Token::Symbol('(') => {
+                let (expr, ty, _) = self.parse_expr(scope)?;
+                self.expect_symbol(')')?;
+                Ok((expr, ty, pos))
+            }
+            Token::Ident(name) if self.eat_symbol('(') => self.parse_call(scope, name, pos),
+            Token::Ident(name) => {
+                if name == "time" {
+                    Ok((Expr::Time, Ty::Float, pos))
+                } else if let Some((expr, ty)) = scope.get(&name) {
+                    Ok((Expr::named(expr), *ty, pos))
+                } else if let Some(value) = constant(&name) {
+                    Ok((Expr::Const(value), value.ty(), pos))
+                } else {
+                    Err(pos.error(format!("unknown name `{name}`")))
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+    /// The opening parenthesis is already read
+    fn parse_call(
+        &mut self,
+        scope: &Scope,
+        name: String,
+        pos: Pos,
+    ) -> Result<(Expr, Ty, Pos), SceneError> {
+        let overloads = FUNCTIONS
+            .iter()
+            .filter(|f| f.name == name)
+            .collect::<Vec<_>>();
+        if overloads.is_empty() {
+            return Err(pos.error(format!("unknown function `{name}`")));
+        }
+        let mut args = Vec::new();
+        let mut types = Vec::new();
+        if !self.eat_symbol(')') {
+            loop {
+                let (arg, ty, _) = self.parse_expr(scope)?;
+                args.push(arg);
+                types.push(ty);
+                if self.eat_symbol(')') {
+                    break;
+                }
+                self.expect_symbol(',')?;
+            }
+        }
+        let Some(function) = overloads.iter().find(|f| f.args == types) else {
+            let signature = |types: &[Ty]| {
+                types
+                    .iter()
+                    .map(|ty| ty.name())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            let expected = overloads
+                .iter()
+                .map(|f| format!("`{name}({})`", signature(f.args)))
+                .collect::<Vec<_>>()
+                .join(" or ");
+            return Err(pos.error(format!(
+                "`{name}` can't take ({}), expected {expected}",
+                signature(&types)
+            )));
+        };
+        Ok((Expr::Call(function, args).fold(), function.output, pos))
+    }
+}
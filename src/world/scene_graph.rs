@@ -0,0 +1,234 @@
+use crate::math::Transform;
+use crate::render_registry::alloc::BufferAllocator;
+use crate::render_registry::materials::MaterialType;
+use crate::render_registry::mesh_builder::VisualExecutor;
+
+use super::primitives::WorldPrimitive;
+use super::variators::{references::Ref, variator::Variator};
+use super::visuals::VisualDirective;
+use super::world::Worlds;
+use super::world_builder::WorldId;
+
+/// A transform of the hierarchy, its world transform is `parent * local`
+#[derive(Clone, Copy)]
+pub struct Node {
+    idx: usize,
+    world: WorldId,
+    global: Ref<Transform>,
+}
+impl Node {
+    /// The world transform, updated each frame
+    pub fn global(self) -> Ref<Transform> {
+        self.global
+    }
+    pub fn world(self) -> WorldId {
+        self.world
+    }
+}
+impl From<Node> for Ref<Transform> {
+    fn from(node: Node) -> Self {
+        node.global
+    }
+}
+impl VisualDirective for Node {
+    fn exec(&self, executor: &mut VisualExecutor) {
+        self.global.exec(executor)
+    }
+    fn alloc(&self, _curr_mty: &mut MaterialType, _alloc: &mut BufferAllocator) {}
+}
+
+#[derive(Clone, Copy)]
+pub enum Parent {
+    Root,
+    Node(Node),
+    /// Any transform, the variators of the same world are read as of the previous frame
+    Ref(Ref<Transform>),
+}
+impl From<Node> for Parent {
+    fn from(node: Node) -> Self {
+        Self::Node(node)
+    }
+}
+impl From<Ref<Transform>> for Parent {
+    fn from(tr: Ref<Transform>) -> Self {
+        Self::Ref(tr)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphError {
+    /// The node belongs to another world
+    ForeignNode,
+    /// The node would become its own ancestor
+    Cycle,
+}
+
+struct NodeState {
+    parent: Parent,
+    local: Box<dyn Variator<Item = Transform>>,
+    global: usize,
+}
+
+/// The nodes of a world, computed parents first
+#[derive(Default)]
+pub struct SceneGraph {
+    /// The world the nodes belong to, none for the graph of an empty world
+    world: Option<WorldId>,
+    nodes: Vec<NodeState>,
+    order: Vec<usize>,
+}
+impl SceneGraph {
+    pub(crate) fn new(world: WorldId) -> Self {
+        Self {
+            world: Some(world),
+            ..Self::default()
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+    /// `global` is the index of the world transform, already allocated in the world
+    pub(crate) fn push(
+        &mut self,
+        global: Ref<Transform>,
+        parent: Parent,
+        local: impl Variator<Item = Transform>,
+    ) -> Result<Node, GraphError> {
+        let node = Node {
+            idx: self.nodes.len(),
+            world: self.world.ok_or(GraphError::ForeignNode)?,
+            global,
+        };
+        self.check_parent(node, parent)?;
+        self.nodes.push(NodeState {
+            parent,
+            local: Box::new(local),
+            global: global.index(),
+        });
+        self.order.push(node.idx);
+        Ok(node)
+    }
+    fn check_node(&self, node: Node) -> Result<(), GraphError> {
+        if self.world != Some(node.world) || node.idx >= self.nodes.len() {
+            return Err(GraphError::ForeignNode);
+        }
+        Ok(())
+    }
+    fn check_parent(&self, node: Node, parent: Parent) -> Result<(), GraphError> {
+        let Parent::Node(mut ancestor) = parent else {
+            return Ok(());
+        };
+        self.check_node(ancestor)?;
+        loop {
+            if ancestor.idx == node.idx {
+                return Err(GraphError::Cycle);
+            }
+            match self.nodes.get(ancestor.idx).map(|n| n.parent) {
+                Some(Parent::Node(next)) => ancestor = next,
+                _ => return Ok(()),
+            }
+        }
+    }
+    /// Moves the node and its subtree under another parent
+    pub fn reparent(&mut self, node: Node, parent: impl Into<Parent>) -> Result<(), GraphError> {
+        let parent = parent.into();
+        self.check_node(node)?;
+        self.check_parent(node, parent)?;
+        self.nodes[node.idx].parent = parent;
+        self.sort();
+        Ok(())
+    }
+    /// Attaches a root node and its subtree under a parent
+    pub fn attach(&mut self, node: Node, parent: impl Into<Parent>) -> Result<(), GraphError> {
+        self.reparent(node, parent)
+    }
+    /// Makes the node a root, its subtree follows its local transform only
+    pub fn detach(&mut self, node: Node) -> Result<(), GraphError> {
+        self.reparent(node, Parent::Root)
+    }
+    pub fn set_local(
+        &mut self,
+        node: Node,
+        local: impl Variator<Item = Transform>,
+    ) -> Result<(), GraphError> {
+        self.check_node(node)?;
+        self.nodes[node.idx].local = Box::new(local);
+        Ok(())
+    }
+    pub fn parent(&self, node: Node) -> Result<Parent, GraphError> {
+        self.check_node(node)?;
+        Ok(self.nodes[node.idx].parent)
+    }
+    /// Depth first order from the roots, parents are computed before their children
+    fn sort(&mut self) {
+        let mut children = vec![Vec::new(); self.nodes.len()];
+        let mut stack = Vec::new();
+        for (i, node) in self.nodes.iter().enumerate() {
+            match node.parent {
+                Parent::Node(parent) => children[parent.idx].push(i),
+                _ => stack.push(i),
+            }
+        }
+        stack.reverse();
+        self.order.clear();
+        while let Some(i) = stack.pop() {
+            self.order.push(i);
+            stack.extend(children[i].iter().rev());
+        }
+    }
+    pub fn update(&self, worlds: &Worlds) {
+        let stores = &worlds.world.stores;
+        for &i in &self.order {
+            let node = &self.nodes[i];
+            let parent = match node.parent {
+                Parent::Root => Transform::ID,
+                Parent::Node(parent) => Transform::get(stores, self.nodes[parent.idx].global),
+                // TODO: evaluate the variators read here first, in dependency order
+                Parent::Ref(tr) => tr.update(worlds),
+            };
+            Transform::set(stores, node.global, parent * node.local.update(worlds));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::world_builder::WorldsBuilder;
+
+    #[test]
+    fn order() {
+        let mut world = WorldsBuilder::default().add_world(0);
+        let a = world.push_node(Parent::Root, Transform::ID);
+        let b = world.push_node(Parent::Root, Transform::ID);
+        let c = world.push_node(a, Transform::ID);
+        let graph = world.scene_graph_mut();
+        graph.reparent(a, b).unwrap();
+        assert_eq!(graph.order, [b.idx, a.idx, c.idx]);
+        assert_eq!(graph.reparent(b, c), Err(GraphError::Cycle));
+        graph.detach(a).unwrap();
+        assert_eq!(graph.order, [a.idx, c.idx, b.idx]);
+    }
+
+    #[test]
+    fn foreign_nodes() {
+        let mut first = WorldsBuilder::default().add_world(0);
+        let a = first.push_node(Parent::Root, Transform::ID);
+        let mut second = first.finalize().add_world(0);
+        let b = second.push_node(Parent::Root, Transform::ID);
+        let graph = second.scene_graph_mut();
+        assert_eq!(
+            graph.reparent(a, Parent::Root),
+            Err(GraphError::ForeignNode)
+        );
+        assert_eq!(graph.reparent(b, a), Err(GraphError::ForeignNode));
+        assert_eq!(
+            graph.set_local(a, Transform::ID),
+            Err(GraphError::ForeignNode)
+        );
+        assert!(matches!(graph.parent(b), Ok(Parent::Root)));
+    }
+}
@@ -1,12 +1,15 @@
 use crate::math::Transform;
+use crate::render_registry::bind_groups_store::StoreBindings;
 use crate::render_registry::materials::MaterialType;
 use crate::render_registry::mesh_builder::VisualExecutor;
 use crate::render_registry::vertex::VertexType;
+use crate::world::lod::{DistanceLod, LodPolicy};
 use crate::world::primitives::camera::Camera;
 use crate::world::variators::variator::Variator;
 use crate::world::visuals::VisualDirective;
 
-use super::primitives::{PrimitiveStoresHolder, StoreLabel, WorldPrimitive};
+use super::primitives::{PrimitiveStoresHolder, WorldPrimitive};
+use super::scene_graph::SceneGraph;
 use super::variators::saved_variator::SavedVariator;
 
 pub struct World {
@@ -15,6 +18,8 @@ pub struct World {
     pub settings: WorldSettings,
     pub directives: Vec<Box<dyn VisualDirective>>,
     pub view_bounding_box: Option<Box<dyn Variator<Item = Transform>>>,
+    pub scene_graph: SceneGraph,
+    pub lod_policy: Box<dyn LodPolicy>,
 }
 
 impl World {
@@ -25,6 +30,8 @@ impl World {
             settings: WorldSettings::default(),
             variators: Vec::new(),
             view_bounding_box: None,
+            scene_graph: SceneGraph::default(),
+            lod_policy: Box::new(DistanceLod::default()),
         }
     }
 
@@ -34,15 +41,17 @@ impl World {
             dir.exec(&mut executor)
         }
     }
+    /// The nodes are computed first, so the variators read their current transforms
     pub fn update_registers(&self, worlds: &Worlds) {
+        self.scene_graph.update(worlds);
+        // TODO: skip the time-independent variators once `Variator` can tell them apart
         for saved_var in &self.variators {
             saved_var.write(worlds);
         }
     }
-    pub fn write_stores(&self, stores: [&mut [u32]; StoreLabel::COUNT]) {
-        for label in StoreLabel::ARRAY {
-            label.write(stores[label as usize], &self.stores);
-        }
+    /// Uploads the values set since the last call
+    pub fn write_stores(&self, bindings: &mut StoreBindings, queue: &wgpu::Queue) {
+        bindings.write(queue, &self.stores);
     }
     pub fn get_cam(&self, idx: usize) -> Camera {
         Camera::get(&self.stores, idx)
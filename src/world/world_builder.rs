@@ -7,10 +7,12 @@ use rand::seq::IndexedRandom;
 use crate::render_registry::alloc::BufferAllocator;
 use crate::render_registry::materials::MaterialType;
 
+use super::lod::{DistanceLod, LodPolicy};
 use super::primitives::camera::{Camera, GetManualCamera};
 use super::world::World;
 use super::{
     primitives::{PrimitivesAllocationTracker, WorldPrimitive},
+    scene_graph::{Node, Parent, SceneGraph},
     variators::{
         references::Ref,
         saved_variator::{SavedVariator, SavedVariatorMultiple, SavedVariatorSingle},
@@ -34,6 +36,8 @@ struct WorldBuildState {
     variators: Vec<Box<dyn SavedVariator>>,
     pub allocs_tracker: PrimitivesAllocationTracker,
     view_bounding_box: Option<Box<dyn Variator<Item = Transform>>>,
+    scene_graph: SceneGraph,
+    lod_policy: Option<Box<dyn LodPolicy>>,
 }
 impl WorldBuildState {
     pub fn allocs(&self) -> BufferAllocator {
@@ -124,9 +128,29 @@ impl WorldBuilder {
             .push(Box::new(SavedVariatorMultiple { index: idx, var }));
         std::array::from_fn(|i| self.make_ref(idx + i))
     }
+    /// A node of the scene graph, its world transform is computed after its parent's each frame
+    pub fn push_node(
+        &mut self,
+        parent: impl Into<Parent>,
+        local: impl Variator<Item = Transform>,
+    ) -> Node {
+        let idx = Transform::alloc(&mut self.state.allocs_tracker, 1);
+        let global = self.make_ref(idx);
+        self.state
+            .scene_graph
+            .push(global, parent.into(), local)
+            .expect("The parent node must be of the same world")
+    }
+    pub fn scene_graph_mut(&mut self) -> &mut SceneGraph {
+        &mut self.state.scene_graph
+    }
     pub fn set_bounding_box(&mut self, v: impl Variator<Item = Transform>) {
         self.state.view_bounding_box = Some(Box::new(v));
     }
+    /// Replaces the default `DistanceLod`
+    pub fn set_lod_policy(&mut self, policy: impl LodPolicy + 'static) {
+        self.state.lod_policy = Some(Box::new(policy));
+    }
 
     pub fn finalize(self) -> WorldsBuilder {
         let mut worlds = self.worlds;
@@ -142,28 +166,41 @@ impl WorldBuilder {
 pub struct WorldBuilderFinalizationValue {
     pub worlds: Vec<World>,
     pub id_by_layer: Vec<Vec<WorldId>>,
-    pub camera_offsets: Vec<usize>,
     pub buffer_allocations: Vec<BufferAllocator>,
 }
 
 pub struct WorldsBuilder {
     worlds: Vec<Option<WorldBuildState>>,
     id_by_layers: Vec<Vec<WorldId>>,
+    /// Id of the first world built, the ones before are already in a scene
+    first_id: usize,
 }
 impl Default for WorldsBuilder {
     fn default() -> Self {
         Self {
             worlds: Vec::new(),
             id_by_layers: Vec::new(),
+            first_id: 0,
         }
     }
 }
 impl WorldsBuilder {
+    /// Builds worlds added to a running scene, after its `first_id` worlds
+    pub fn continuing(first_id: usize) -> Self {
+        Self {
+            worlds: (0..first_id).map(|_| None).collect(),
+            first_id,
+            ..Self::default()
+        }
+    }
     pub fn add_world(mut self, layer: usize) -> WorldBuilder {
         let id = WorldId(self.worlds.len());
         self.worlds.push(None);
         WorldBuilder {
-            state: WorldBuildState::default(),
+            state: WorldBuildState {
+                scene_graph: SceneGraph::new(id),
+                ..WorldBuildState::default()
+            },
             worlds: self,
             id,
             layer,
@@ -175,6 +212,7 @@ impl WorldsBuilder {
         f(&mut builder);
         *self = builder.finalize();
     }
+    /// The manual camera is put last in the last world
     pub fn finalize(mut self) -> WorldBuilderFinalizationValue {
         let w = self.worlds.last_mut().unwrap().as_mut().unwrap();
         let idx = Camera::alloc(&mut w.allocs_tracker, 1);
@@ -182,40 +220,33 @@ impl WorldsBuilder {
             index: idx,
             var: GetManualCamera,
         }));
-
-        let allocs = self
-            .worlds
-            .iter()
-            .map(|wopt| match wopt {
-                None => BufferAllocator::new(),
-                Some(state) => state.allocs(),
-            })
-            .collect();
-
-        let mut camera_offsets = vec![0; self.worlds.len() + 1];
-        for i in 0..self.worlds.len() {
-            camera_offsets[i + 1] =
-                camera_offsets[i] + &self.worlds[i].as_ref().map(|w| w.allocs_tracker.camera).unwrap_or(0)
-        }
-
-        let worlds = self
-            .worlds
-            .into_iter()
+        self.finalize_added()
+    }
+    /// Only the worlds built from `first_id`, without a manual camera
+    pub fn finalize_added(self) -> WorldBuilderFinalizationValue {
+        let added = self.worlds.into_iter().skip(self.first_id);
+        let (allocs, worlds) = added
             .map(|wopt| match wopt {
-                None => World::new(),
-                Some(state) => World {
-                    directives: state.directives,
-                    stores: state.allocs_tracker.to_store_holder(),
-                    variators: state.variators,
-                    view_bounding_box: state.view_bounding_box,
-                    ..World::new()
-                },
+                None => (BufferAllocator::new(), World::new()),
+                Some(state) => (
+                    state.allocs(),
+                    World {
+                        directives: state.directives,
+                        stores: state.allocs_tracker.to_store_holder(),
+                        variators: state.variators,
+                        view_bounding_box: state.view_bounding_box,
+                        scene_graph: state.scene_graph,
+                        lod_policy: state
+                            .lod_policy
+                            .unwrap_or_else(|| Box::new(DistanceLod::default())),
+                        ..World::new()
+                    },
+                ),
             })
-            .collect();
+            .unzip();
 
         WorldBuilderFinalizationValue {
             worlds,
-            camera_offsets,
             id_by_layer: self.id_by_layers,
             buffer_allocations: allocs,
         }